@@ -0,0 +1,22 @@
+//! Compares writing two `uint64` values packed into one slot against two separate slots.
+
+use alloy_primitives::U256;
+
+use tempo_storage_interop::{GasComparison, Handler, Slot};
+
+fn main() -> tempo_storage_interop::Result<()> {
+    let comparison = GasComparison::run(
+        |storage| {
+            Slot::<u64>::new(U256::ZERO).write(storage, 1)?;
+            Slot::<u64>::new(U256::ONE).write(storage, 2)
+        },
+        |storage| Slot::<(u64, u64)>::new(U256::ZERO).write(storage, (1, 2)),
+    )?;
+
+    println!("separate slots: {} gas", comparison.baseline);
+    println!("packed struct:  {} gas", comparison.candidate);
+    println!("delta:          {} gas", comparison.delta());
+
+    assert!(comparison.delta() < 0, "packing should save gas");
+    Ok(())
+}
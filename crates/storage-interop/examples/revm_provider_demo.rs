@@ -1,29 +1,23 @@
 #[cfg(feature = "revm")]
 mod demo {
-    use alloy_evm::{EvmEnv, EvmFactory, EvmInternals};
+    use alloy_evm::{EthEvmFactory, EvmEnv, EvmFactory, EvmInternals};
     use alloy_primitives::{Address, U256};
     use revm::context::CfgEnv;
     use revm::database::{CacheDB, EmptyDB};
     use revm::primitives::hardfork::SpecId;
 
-    use tempo_storage_interop::{RevmStorageProvider, RuntimeContext, Slot};
+    use tempo_storage_interop::{Handler, RevmStorageProvider, RuntimeContext, Slot};
 
     pub fn run() -> tempo_storage_interop::Result<()> {
         let db = CacheDB::new(EmptyDB::new());
-        let mut evm = EvmFactory::default().create_evm(db, EvmEnv::default());
+        let mut evm = EthEvmFactory::default().create_evm(db, EvmEnv::default());
         let ctx = evm.ctx_mut();
 
+        let cfg = CfgEnv::<SpecId>::new_with_spec(ctx.cfg.spec).with_chain_id(ctx.cfg.chain_id);
         let internals = EvmInternals::new(&mut ctx.journaled_state, &ctx.block);
-        let mut provider = RevmStorageProvider::new_max_gas(
-            internals,
-            &CfgEnv::<SpecId> {
-                chain_id: ctx.cfg.chain_id,
-                spec: ctx.cfg.spec,
-                ..Default::default()
-            },
-        );
-
-        let contract = Address::random();
+        let mut provider = RevmStorageProvider::new_max_gas(internals, &cfg);
+
+        let contract = Address::repeat_byte(0xc0);
         let mut runtime = RuntimeContext::new(&mut provider, contract);
         let mut ops = runtime.storage_ops();
 
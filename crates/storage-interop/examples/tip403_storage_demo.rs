@@ -1,101 +1,62 @@
-use std::collections::HashMap;
-
 use alloy_primitives::{Address, U256};
 
 use tempo_storage_interop::{
-    FieldLocation, StorageKey, StorageOps, extract_packed_value, insert_packed_value,
+    FieldLocation, Handler, Mapping, MemoryStorage, StorageKey, StorageOps, StructLayout,
+    extract_packed_value, insert_packed_value, layout_of, storable_struct,
 };
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct PolicyData {
-    policy_type: u8,
-    admin: Address,
-}
-
-impl PolicyData {
-    const POLICY_TYPE_LOC: FieldLocation = FieldLocation::new(0, 0, 1);
-    const ADMIN_LOC: FieldLocation = FieldLocation::new(0, 1, 20);
-
-    fn encode(&self) -> tempo_storage_interop::Result<U256> {
-        let encoded = insert_packed_value(
-            U256::ZERO,
-            &self.policy_type,
-            Self::POLICY_TYPE_LOC.offset_bytes,
-            Self::POLICY_TYPE_LOC.size,
-        )?;
-
-        insert_packed_value(
-            encoded,
-            &self.admin,
-            Self::ADMIN_LOC.offset_bytes,
-            Self::ADMIN_LOC.size,
-        )
-    }
-
-    fn decode(slot: U256) -> tempo_storage_interop::Result<Self> {
-        let policy_type = extract_packed_value::<u8>(
-            slot,
-            Self::POLICY_TYPE_LOC.offset_bytes,
-            Self::POLICY_TYPE_LOC.size,
-        )?;
-        let admin = extract_packed_value::<Address>(
-            slot,
-            Self::ADMIN_LOC.offset_bytes,
-            Self::ADMIN_LOC.size,
-        )?;
-        Ok(Self { policy_type, admin })
-    }
-}
-
-#[derive(Default)]
-struct MemoryStorage {
-    slots: HashMap<U256, U256>,
-}
-
-impl StorageOps for MemoryStorage {
-    fn load(&self, slot: U256) -> tempo_storage_interop::Result<U256> {
-        Ok(*self.slots.get(&slot).unwrap_or(&U256::ZERO))
+storable_struct! {
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct PolicyData {
+        policy_type: u8,
+        admin: Address,
     }
-
-    fn store(&mut self, slot: U256, value: U256) -> tempo_storage_interop::Result<()> {
-        self.slots.insert(slot, value);
-        Ok(())
-    }
-}
-
-fn mapping_slot(policy_id: U256, base_slot: U256) -> U256 {
-    policy_id.mapping_slot(base_slot)
 }
 
 fn main() -> tempo_storage_interop::Result<()> {
     let mut storage = MemoryStorage::default();
 
-    // Simplified TIP403 layout
-    let policy_data_base_slot = U256::from(1);
+    // Simplified TIP403 layout: mapping(uint256 => PolicyData) at slot 1
+    let policy_data = Mapping::<U256, PolicyData>::new(U256::from(1));
     let policy_id = U256::from(2);
 
     let data = PolicyData {
         policy_type: 1,
-        admin: Address::random(),
+        admin: Address::repeat_byte(0xad),
     };
 
-    let slot = mapping_slot(policy_id, policy_data_base_slot);
-    storage.store(slot, data.encode()?)?;
+    let mut entry = policy_data.at(policy_id);
+    entry.write(&mut storage, data.clone())?;
+    assert_eq!(entry.read(&storage)?, data);
 
+    // Both fields are packed into the single mapping slot
+    let slot = policy_id.mapping_slot(U256::from(1));
+    assert_eq!(entry.slot(), slot);
     let loaded_raw = storage.load(slot)?;
-    let decoded = PolicyData::decode(loaded_raw)?;
-    assert_eq!(decoded, data);
 
     // Example of packed field access: policy_type at offset 0
-    let policy_type = extract_packed_value::<u8>(loaded_raw, 0, 1)?;
+    let (_, ctx) = PolicyData::field_slot(slot, "policy_type").unwrap();
+    let policy_type = extract_packed_value::<u8>(loaded_raw, ctx.packed_offset().unwrap(), 1)?;
     assert_eq!(policy_type, 1);
 
     // Example of manual packed write into slot
     let updated = insert_packed_value(loaded_raw, &2u8, 0, 1)?;
     storage.store(slot, updated)?;
-    let updated_raw = storage.load(slot)?;
-    let updated_data = PolicyData::decode(updated_raw)?;
-    assert_eq!(updated_data.policy_type, 2);
+    assert_eq!(entry.read(&storage)?.policy_type, 2);
+
+    // Without the macro, layout_of derives the same locations from the field sizes alone
+    const LOCS: [FieldLocation; 2] = layout_of([1, 20]);
+    let admin_loc = LOCS[1];
+    let (admin_slot, ctx) = PolicyData::field_slot(slot, "admin").unwrap();
+    assert_eq!(admin_slot, slot + U256::from(admin_loc.offset_slots));
+    assert_eq!(ctx.packed_offset(), Some(admin_loc.offset_bytes));
+
+    let admin = extract_packed_value::<Address>(
+        storage.load(slot)?,
+        admin_loc.offset_bytes,
+        admin_loc.size,
+    )?;
+    assert_eq!(admin, data.admin);
 
     Ok(())
 }
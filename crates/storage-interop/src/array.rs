@@ -2,10 +2,12 @@ use alloy_primitives::U256;
 use std::marker::PhantomData;
 
 use crate::{
+    iter::ElementIter,
     layout::{Handler, Layout, LayoutCtx, Storable, StorableType},
     packing,
     slot::Slot,
-    storage::StorageOps,
+    storage::{SkipZeroWrites, StorageOps, prefetch_range},
+    InteropError,
     Result,
 };
 
@@ -29,6 +31,12 @@ where
         }
     }
 
+    /// Equivalent to [`ArrayHandler::new`], with the first element at `slot`.
+    #[inline]
+    pub fn at_slot(slot: U256) -> Self {
+        Self::new(slot)
+    }
+
     #[inline]
     fn as_slot(&self) -> Slot<[T; N]> {
         Slot::new(self.base_slot)
@@ -55,21 +63,68 @@ where
             return None;
         }
 
-        let (base_slot, layout_ctx) = if T::BYTES <= 16 {
-            let location = packing::calc_element_loc(index, T::BYTES);
-            (
-                self.base_slot + U256::from(location.offset_slots),
-                LayoutCtx::packed(location.offset_bytes),
-            )
+        let (base_slot, offset) =
+            packing::fixed_array_element_slot(self.base_slot, index, T::BYTES);
+        let layout_ctx = if T::BYTES <= 16 {
+            LayoutCtx::packed(offset)
         } else {
-            (
-                self.base_slot + U256::from(index * T::SLOTS),
-                LayoutCtx::FULL,
-            )
+            LayoutCtx::FULL
         };
 
         Some(T::handle(base_slot, layout_ctx))
     }
+
+    /// Overwrites the element at `index`, leaving packed neighbours untouched.
+    pub fn set<S: StorageOps>(&mut self, storage: &mut S, index: usize, value: T) -> Result<()>
+    where
+        T: Storable,
+    {
+        if index >= N {
+            return Err(InteropError::IndexOutOfBounds { index, len: N });
+        }
+
+        if T::BYTES <= 16 {
+            packing::write_packed_element(storage, self.base_slot, index, T::BYTES, &value)
+        } else {
+            let (slot, _) = packing::fixed_array_element_slot(self.base_slot, index, T::BYTES);
+            value.store(storage, slot, LayoutCtx::FULL)
+        }
+    }
+
+    /// Reads the array element by element into `out`, avoiding the `[T; N]` temporary of `read`
+    /// for large arrays. `out` must hold exactly `N` elements.
+    pub fn read_into<S: StorageOps>(&self, storage: &S, out: &mut [T]) -> Result<()>
+    where
+        T: Storable,
+    {
+        if out.len() != N {
+            return Err(InteropError::LengthMismatch {
+                expected: N,
+                actual: out.len(),
+            });
+        }
+
+        prefetch_range(storage, self.base_slot, <[T; N]>::SLOTS)?;
+        for (slot, value) in out.iter_mut().zip(self.iter(storage)) {
+            *slot = value?;
+        }
+        Ok(())
+    }
+
+    /// Iterates over the elements in slot order, loading each packed slot only once.
+    #[inline]
+    pub fn iter<'a, S: StorageOps>(&self, storage: &'a S) -> ElementIter<'a, T, S>
+    where
+        T: Storable,
+    {
+        ElementIter::new(storage, self.base_slot, N)
+    }
+}
+
+impl<T: StorableType, const N: usize> Default for ArrayHandler<T, N> {
+    fn default() -> Self {
+        Self::new(U256::ZERO)
+    }
 }
 
 impl<T, const N: usize> Handler<[T; N]> for ArrayHandler<T, N>
@@ -90,6 +145,29 @@ where
     }
 }
 
+impl<T, const N: usize> ArrayHandler<T, N>
+where
+    T: StorableType,
+    [T; N]: Storable,
+{
+    /// Like `write`, but skips every store that would write a zero word.
+    ///
+    /// Only correct when all slots covered by `value` are known to be zero already (e.g. first
+    /// initialization). Otherwise stale non-zero data is left in place of zero elements.
+    pub fn store_nonzero_only<S: StorageOps>(
+        &mut self,
+        storage: &mut S,
+        value: [T; N],
+    ) -> Result<()> {
+        value.store(
+            &mut SkipZeroWrites::new(storage),
+            self.base_slot,
+            LayoutCtx::FULL,
+        )
+    }
+}
+
+/// Zero-length arrays occupy `Slots(0)`: loading, storing and deleting them never touch storage.
 impl<T, const N: usize> StorableType for [T; N]
 where
     T: Storable,
@@ -102,7 +180,8 @@ where
 
     type Handler = ArrayHandler<T, N>;
 
-    fn handle(slot: U256, _ctx: LayoutCtx) -> Self::Handler {
+    fn handle(slot: U256, ctx: LayoutCtx) -> Self::Handler {
+        debug_assert_eq!(ctx, LayoutCtx::FULL, "Arrays cannot be packed");
         ArrayHandler::new(slot)
     }
 }
@@ -114,6 +193,7 @@ where
     fn load<S: StorageOps>(storage: &S, base_slot: U256, ctx: LayoutCtx) -> Result<Self> {
         debug_assert_eq!(ctx, LayoutCtx::FULL, "Arrays cannot be packed");
 
+        prefetch_range(storage, base_slot, Self::SLOTS)?;
         if T::BYTES <= 16 {
             load_packed_array(storage, base_slot)
         } else {
@@ -141,13 +221,27 @@ where
             }
         } else {
             for index in 0..N {
-                let slot = base_slot + U256::from(index * T::SLOTS);
+                let slot = base_slot + packing::element_slot_offset(index, T::SLOTS);
                 T::delete(storage, slot, LayoutCtx::FULL)?;
             }
         }
 
         Ok(())
     }
+
+    fn raw_slots<S: StorageOps>(storage: &S, base_slot: U256) -> Result<Vec<U256>> {
+        if T::BYTES <= 16 {
+            let slot_count = packing::calc_packed_slot_count(N, T::BYTES);
+            return Ok((0..slot_count).map(|i| base_slot + U256::from(i)).collect());
+        }
+
+        let mut slots = Vec::with_capacity(Self::SLOTS);
+        for index in 0..N {
+            let slot = base_slot + packing::element_slot_offset(index, T::SLOTS);
+            slots.extend(T::raw_slots(storage, slot)?);
+        }
+        Ok(slots)
+    }
 }
 
 fn load_packed_array<T, const N: usize, S: StorageOps>(
@@ -163,7 +257,8 @@ where
     for index in 0..N {
         let loc = packing::calc_element_loc(index, T::BYTES);
         let slot = base_slot + U256::from(loc.offset_slots);
-        let value = T::load(storage, slot, LayoutCtx::packed(loc.offset_bytes))?;
+        let value = T::load(storage, slot, LayoutCtx::packed(loc.offset_bytes))
+            .map_err(|err| err.with_slot(slot))?;
         data[index].write(value);
     }
 
@@ -181,7 +276,7 @@ where
         std::array::from_fn(|_| std::mem::MaybeUninit::uninit());
 
     for index in 0..N {
-        let slot = base_slot + U256::from(index * T::SLOTS);
+        let slot = base_slot + packing::element_slot_offset(index, T::SLOTS);
         let value = T::load(storage, slot, LayoutCtx::FULL)?;
         data[index].write(value);
     }
@@ -200,7 +295,9 @@ where
     for index in 0..N {
         let loc = packing::calc_element_loc(index, T::BYTES);
         let slot = base_slot + U256::from(loc.offset_slots);
-        values[index].store(storage, slot, LayoutCtx::packed(loc.offset_bytes))?;
+        values[index]
+            .store(storage, slot, LayoutCtx::packed(loc.offset_bytes))
+            .map_err(|err| err.with_slot(slot))?;
     }
     Ok(())
 }
@@ -214,8 +311,148 @@ where
     T: Storable,
 {
     for index in 0..N {
-        let slot = base_slot + U256::from(index * T::SLOTS);
+        let slot = base_slot + packing::element_slot_offset(index, T::SLOTS);
         values[index].store(storage, slot, LayoutCtx::FULL)?;
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{CountingStorage, MemoryStorage};
+
+    #[test]
+    fn test_store_nonzero_only_packed() -> Result<()> {
+        let mut storage = CountingStorage::default();
+        let mut handler = ArrayHandler::<u64, 8>::new(U256::ZERO);
+        let values = [1, 0, 0, 2, 0, 0, 0, 0];
+
+        handler.store_nonzero_only(&mut storage, values)?;
+        // elements 0 and 3 share the first slot, the second slot stays untouched
+        assert_eq!(storage.stores(), 2);
+        assert_eq!(handler.read(&storage)?, values);
+        Ok(())
+    }
+
+    #[test]
+    fn test_zero_length_arrays() -> Result<()> {
+        assert_eq!(<[u8; 0]>::LAYOUT, Layout::Slots(0));
+        assert_eq!(<[U256; 0]>::LAYOUT, Layout::Slots(0));
+
+        let mut storage = CountingStorage::default();
+        let mut packed = ArrayHandler::<u8, 0>::new(U256::from(3));
+        let mut unpacked = ArrayHandler::<U256, 0>::new(U256::from(3));
+
+        packed.write(&mut storage, [])?;
+        unpacked.write(&mut storage, [])?;
+        assert_eq!(packed.read(&storage)?, [0u8; 0]);
+        assert_eq!(unpacked.read(&storage)?, [U256::ZERO; 0]);
+        packed.delete(&mut storage)?;
+        unpacked.delete(&mut storage)?;
+
+        assert!(packed.at(0).is_none());
+        assert_eq!((storage.loads(), storage.stores()), (0, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_into_heap_buffer() -> Result<()> {
+        let base = U256::from(9);
+        let mut storage: MemoryStorage = (0..1024)
+            .map(|i| (base + U256::from(i), U256::from(i + 1)))
+            .collect();
+        let handler = ArrayHandler::<U256, 1024>::new(base);
+
+        let mut out = vec![U256::ZERO; 1024];
+        handler.read_into(&storage, &mut out)?;
+        assert!(out.iter().enumerate().all(|(i, v)| *v == U256::from(i + 1)));
+
+        assert!(matches!(
+            handler.read_into(&storage, &mut out[..10]),
+            Err(InteropError::LengthMismatch {
+                expected: 1024,
+                actual: 10
+            })
+        ));
+
+        let mut packed = ArrayHandler::<u16, 40>::new(U256::ZERO);
+        packed.write(&mut storage, std::array::from_fn(|i| i as u16))?;
+        let mut out = vec![0u16; 40];
+        packed.read_into(&storage, &mut out)?;
+        assert_eq!(out, (0..40).collect::<Vec<u16>>());
+        Ok(())
+    }
+
+    #[test]
+    fn test_iter_loads_each_packed_slot_once() -> Result<()> {
+        let mut storage = CountingStorage::default();
+        let mut handler = ArrayHandler::<u8, 64>::new(U256::from(7));
+        let values: [u8; 64] = std::array::from_fn(|i| i as u8);
+        handler.write(&mut storage, values)?;
+
+        storage.reset();
+        let loaded = handler.iter(&storage).collect::<Result<Vec<_>>>()?;
+        assert_eq!(loaded, values);
+        assert_eq!(storage.loads(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_iter_unpacked() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+        let mut handler = ArrayHandler::<U256, 3>::new(U256::ZERO);
+        let values = [U256::from(1), U256::from(2), U256::MAX];
+        handler.write(&mut storage, values)?;
+
+        assert_eq!(handler.iter(&storage).collect::<Result<Vec<_>>>()?, values);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fixed_array_element_slot_matches_handler() {
+        let base = U256::from(10);
+
+        let packed = ArrayHandler::<u16, 40>::new(base);
+        for index in [0, 15, 16, 39] {
+            let slot = packed.at(index).unwrap();
+            let expected = (slot.slot(), slot.offset().unwrap());
+            assert_eq!(packing::fixed_array_element_slot(base, index, 2), expected);
+        }
+
+        let unpacked = ArrayHandler::<U256, 4>::new(base);
+        for index in 0..4 {
+            let expected = (unpacked.at(index).unwrap().slot(), 0);
+            assert_eq!(packing::fixed_array_element_slot(base, index, 32), expected);
+        }
+
+        let nested = ArrayHandler::<[U256; 2], 3>::new(base);
+        assert_eq!(
+            packing::fixed_array_element_slot(base, 2, 64),
+            (nested.at(2).unwrap().base_slot(), 0)
+        );
+    }
+
+    #[test]
+    fn test_set_out_of_bounds() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+        let mut handler = ArrayHandler::<u32, 10>::new(U256::ONE);
+        handler.set(&mut storage, 9, 7)?;
+        handler.set(&mut storage, 8, 6)?;
+        assert_eq!(handler.read(&storage)?[8..], [6, 7]);
+
+        let err = handler.set(&mut storage, 10, 1).unwrap_err();
+        assert!(matches!(
+            err,
+            InteropError::IndexOutOfBounds { index: 10, len: 10 }
+        ));
+        assert_eq!(err.to_string(), "index 10 out of bounds for length 10");
+
+        let mut unpacked = ArrayHandler::<U256, 2>::new(U256::from(20));
+        unpacked.set(&mut storage, 1, U256::MAX)?;
+        assert_eq!(unpacked.read(&storage)?, [U256::ZERO, U256::MAX]);
+        let err = unpacked.set(&mut storage, 5, U256::ONE).unwrap_err();
+        assert!(err.to_string().contains("index 5") && err.to_string().contains("length 2"));
+        Ok(())
+    }
+}
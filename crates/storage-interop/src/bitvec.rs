@@ -0,0 +1,268 @@
+//! Bit-packed boolean vector for Rust-only storage.
+//!
+//! **Not Solidity-compatible**: `bool[]` stores one byte per element, whereas [`BitVec`] packs
+//! 256 bits per slot. The length lives at the base slot and the bits start at
+//! `keccak256(base_slot)`, with bit `i` stored in slot `i / 256` at bit position `i % 256`.
+
+use alloy_primitives::U256;
+
+use crate::{
+    InteropError, Result,
+    layout::{Handler, Layout, LayoutCtx, Storable, StorableType},
+    packing::{PREALLOCATION_LIMIT, data_region_slot, decode_length},
+    slot::Slot,
+    storage::StorageOps,
+};
+
+const BITS_PER_SLOT: usize = 256;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BitVec(pub Vec<bool>);
+
+impl StorableType for BitVec {
+    const LAYOUT: Layout = Layout::Slots(1);
+    const IS_DYNAMIC: bool = true;
+    type Handler = BitVecHandler;
+
+    fn handle(slot: U256, ctx: LayoutCtx) -> Self::Handler {
+        debug_assert_eq!(ctx, LayoutCtx::FULL, "BitVec cannot be packed");
+        BitVecHandler::new(slot)
+    }
+}
+
+impl Storable for BitVec {
+    fn load<S: StorageOps>(storage: &S, len_slot: U256, ctx: LayoutCtx) -> Result<Self> {
+        debug_assert_eq!(ctx, LayoutCtx::FULL, "BitVec cannot be packed");
+
+        let length = decode_length(storage.load(len_slot)?)?;
+        let data_start = data_region_slot(len_slot);
+        let mut bits = Vec::with_capacity(length.min(PREALLOCATION_LIMIT));
+
+        for slot_idx in 0..length.div_ceil(BITS_PER_SLOT) {
+            let word = storage.load(data_start + U256::from(slot_idx))?;
+            let count = (length - slot_idx * BITS_PER_SLOT).min(BITS_PER_SLOT);
+            bits.extend((0..count).map(|bit| word.bit(bit)));
+        }
+
+        Ok(Self(bits))
+    }
+
+    fn store<S: StorageOps>(&self, storage: &mut S, len_slot: U256, ctx: LayoutCtx) -> Result<()> {
+        debug_assert_eq!(ctx, LayoutCtx::FULL, "BitVec cannot be packed");
+
+        let old_length = decode_length(storage.load(len_slot)?)?;
+        storage.store(len_slot, U256::from(self.0.len()))?;
+
+        let data_start = data_region_slot(len_slot);
+        for (slot_idx, chunk) in self.0.chunks(BITS_PER_SLOT).enumerate() {
+            let mut word = U256::ZERO;
+            for (bit, value) in chunk.iter().enumerate() {
+                word.set_bit(bit, *value);
+            }
+            storage.store(data_start + U256::from(slot_idx), word)?;
+        }
+
+        // clear slots of a previous, longer value so its bits can't resurface on a later push
+        let new_slots = self.0.len().div_ceil(BITS_PER_SLOT);
+        for slot_idx in new_slots..old_length.div_ceil(BITS_PER_SLOT) {
+            storage.store(data_start + U256::from(slot_idx), U256::ZERO)?;
+        }
+
+        Ok(())
+    }
+
+    fn delete<S: StorageOps>(storage: &mut S, len_slot: U256, ctx: LayoutCtx) -> Result<()> {
+        debug_assert_eq!(ctx, LayoutCtx::FULL, "BitVec cannot be packed");
+
+        let length = decode_length(storage.load(len_slot)?)?;
+        storage.store(len_slot, U256::ZERO)?;
+
+        let data_start = data_region_slot(len_slot);
+        for slot_idx in 0..length.div_ceil(BITS_PER_SLOT) {
+            storage.store(data_start + U256::from(slot_idx), U256::ZERO)?;
+        }
+
+        Ok(())
+    }
+
+    fn raw_slots<S: StorageOps>(storage: &S, len_slot: U256) -> Result<Vec<U256>> {
+        let length = decode_length(storage.load(len_slot)?)?;
+        let data_start = data_region_slot(len_slot);
+
+        let mut slots = vec![len_slot];
+        slots.extend((0..length.div_ceil(BITS_PER_SLOT)).map(|i| data_start + U256::from(i)));
+        Ok(slots)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BitVecHandler {
+    len_slot: U256,
+}
+
+impl BitVecHandler {
+    #[inline]
+    pub fn new(len_slot: U256) -> Self {
+        Self { len_slot }
+    }
+
+    /// Equivalent to [`BitVecHandler::new`].
+    #[inline]
+    pub fn at_slot(slot: U256) -> Self {
+        Self::new(slot)
+    }
+
+    #[inline]
+    pub fn len_slot(&self) -> U256 {
+        self.len_slot
+    }
+
+    #[inline]
+    pub fn len<S: StorageOps>(&self, storage: &S) -> Result<usize> {
+        decode_length(storage.load(self.len_slot)?)
+    }
+
+    #[inline]
+    pub fn is_empty<S: StorageOps>(&self, storage: &S) -> Result<bool> {
+        Ok(self.len(storage)? == 0)
+    }
+
+    /// Returns the bit at `index`, or `None` if it is out of bounds.
+    pub fn get<S: StorageOps>(&self, storage: &S, index: usize) -> Result<Option<bool>> {
+        if index >= self.len(storage)? {
+            return Ok(None);
+        }
+
+        let (slot, bit) = self.bit_location(index);
+        Ok(Some(storage.load(slot)?.bit(bit)))
+    }
+
+    /// Sets the bit at `index` with a single read-modify-write of its slot.
+    pub fn set<S: StorageOps>(&mut self, storage: &mut S, index: usize, value: bool) -> Result<()> {
+        let len = self.len(storage)?;
+        if index >= len {
+            return Err(InteropError::IndexOutOfBounds { index, len });
+        }
+
+        self.write_bit(storage, index, value)
+    }
+
+    pub fn push<S: StorageOps>(&mut self, storage: &mut S, value: bool) -> Result<()> {
+        let len = self.len(storage)?;
+        self.write_bit(storage, len, value)?;
+        storage.store(self.len_slot, U256::from(len + 1))
+    }
+
+    fn write_bit<S: StorageOps>(&self, storage: &mut S, index: usize, value: bool) -> Result<()> {
+        let (slot, bit) = self.bit_location(index);
+        let mut word = storage.load(slot)?;
+        word.set_bit(bit, value);
+        storage.store(slot, word)
+    }
+
+    #[inline]
+    fn bit_location(&self, index: usize) -> (U256, usize) {
+        let slot = data_region_slot(self.len_slot) + U256::from(index / BITS_PER_SLOT);
+        (slot, index % BITS_PER_SLOT)
+    }
+}
+
+impl Default for BitVecHandler {
+    fn default() -> Self {
+        Self::new(U256::ZERO)
+    }
+}
+
+impl Handler<BitVec> for BitVecHandler {
+    fn read<S: StorageOps>(&self, storage: &S) -> Result<BitVec> {
+        Slot::<BitVec>::new(self.len_slot).read(storage)
+    }
+
+    fn write<S: StorageOps>(&mut self, storage: &mut S, value: BitVec) -> Result<()> {
+        Slot::<BitVec>::new(self.len_slot).write(storage, value)
+    }
+
+    fn delete<S: StorageOps>(&mut self, storage: &mut S) -> Result<()> {
+        Slot::<BitVec>::new(self.len_slot).delete(storage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::MemoryStorage;
+
+    #[test]
+    fn test_set_across_slot_boundary() -> Result<()> {
+        let mut storage = MemoryStorage::new();
+        let mut handler = BitVecHandler::new(U256::from(1));
+        handler.write(&mut storage, BitVec(vec![false; 300]))?;
+
+        for index in [0, 255, 256, 299] {
+            handler.set(&mut storage, index, true)?;
+        }
+        handler.set(&mut storage, 255, false)?;
+
+        let data_start = data_region_slot(U256::from(1));
+        assert_eq!(storage.load(data_start)?, U256::ONE);
+        assert_eq!(
+            storage.load(data_start + U256::ONE)?,
+            U256::ONE | (U256::ONE << 43)
+        );
+
+        let bits = handler.read(&storage)?.0;
+        let set: Vec<usize> = (0..bits.len()).filter(|i| bits[*i]).collect();
+        assert_eq!(set, vec![0, 256, 299]);
+        assert_eq!(handler.get(&storage, 256)?, Some(true));
+        assert_eq!(handler.get(&storage, 300)?, None);
+        assert!(matches!(
+            handler.set(&mut storage, 300, true),
+            Err(InteropError::IndexOutOfBounds {
+                index: 300,
+                len: 300
+            })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_push_and_delete() -> Result<()> {
+        let mut storage = MemoryStorage::new();
+        let mut handler = BitVecHandler::new(U256::ZERO);
+
+        let expected: Vec<bool> = (0..260).map(|i| i % 3 == 0).collect();
+        for bit in &expected {
+            handler.push(&mut storage, *bit)?;
+        }
+        assert_eq!(handler.len(&storage)?, 260);
+        assert_eq!(handler.read(&storage)?, BitVec(expected));
+
+        handler.delete(&mut storage)?;
+        assert!(storage.dump_nonzero().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_shrink_zeroes_stale_slots() -> Result<()> {
+        let mut storage = MemoryStorage::new();
+        let mut handler = BitVecHandler::new(U256::from(3));
+        handler.write(&mut storage, BitVec(vec![true; 600]))?;
+        handler.write(&mut storage, BitVec(vec![true; 10]))?;
+
+        let data_start = data_region_slot(U256::from(3));
+        assert_eq!(storage.load(data_start)?, U256::from(0x3ff));
+        assert_eq!(storage.load(data_start + U256::ONE)?, U256::ZERO);
+        assert_eq!(storage.load(data_start + U256::from(2))?, U256::ZERO);
+
+        // growing again must not bring back the old bits
+        for _ in 0..256 {
+            handler.push(&mut storage, false)?;
+        }
+        assert_eq!(handler.get(&storage, 265)?, Some(false));
+        assert_eq!(
+            handler.read(&storage)?.0.iter().filter(|bit| **bit).count(),
+            10
+        );
+        Ok(())
+    }
+}
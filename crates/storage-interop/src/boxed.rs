@@ -0,0 +1,121 @@
+//! [`Boxed<T>`], a heap-allocated value stored exactly like `T`, so generic code can hold large
+//! or recursive values behind a pointer.
+//!
+//! `Box<T>` itself can't implement [`Storable`]: `Box` is `#[fundamental]`, so such an impl
+//! would overlap with the blanket impl for [`Packable`](crate::Packable) types.
+
+use alloy_primitives::U256;
+use std::ops::{Deref, DerefMut};
+
+use crate::{
+    Result,
+    describe::{DescribeLayout, StorageLayout},
+    layout::{Layout, LayoutCtx, Storable, StorableType},
+    slot::Slot,
+    storage::StorageOps,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Boxed<T>(pub Box<T>);
+
+impl<T> Boxed<T> {
+    pub fn new(value: T) -> Self {
+        Self(Box::new(value))
+    }
+
+    pub fn into_inner(self) -> T {
+        *self.0
+    }
+}
+
+impl<T> From<T> for Boxed<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T> Deref for Boxed<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Boxed<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Storable> StorableType for Boxed<T> {
+    const LAYOUT: Layout = T::LAYOUT;
+    const IS_DYNAMIC: bool = T::IS_DYNAMIC;
+    type Handler = Slot<Self>;
+
+    fn handle(slot: U256, ctx: LayoutCtx) -> Self::Handler {
+        Slot::new_with_ctx(slot, ctx)
+    }
+}
+
+impl<T: Storable> Storable for Boxed<T> {
+    #[inline]
+    fn load<S: StorageOps>(storage: &S, slot: U256, ctx: LayoutCtx) -> Result<Self> {
+        T::load(storage, slot, ctx).map(Self::new)
+    }
+
+    #[inline]
+    fn store<S: StorageOps>(&self, storage: &mut S, slot: U256, ctx: LayoutCtx) -> Result<()> {
+        (**self).store(storage, slot, ctx)
+    }
+
+    #[inline]
+    fn delete<S: StorageOps>(storage: &mut S, slot: U256, ctx: LayoutCtx) -> Result<()> {
+        T::delete(storage, slot, ctx)
+    }
+
+    fn raw_slots<S: StorageOps>(storage: &S, slot: U256) -> Result<Vec<U256>> {
+        T::raw_slots(storage, slot)
+    }
+}
+
+impl<T: DescribeLayout + Storable> DescribeLayout for Boxed<T> {
+    fn storage_layout() -> StorageLayout {
+        T::storage_layout()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Handler, Mapping, StorageKey, test_util::MemoryStorage};
+
+    #[test]
+    fn test_boxed_array_round_trip() -> Result<()> {
+        let values = Boxed::new([1, 2, 3, 4].map(U256::from));
+        let mut storage = MemoryStorage::new();
+        let mut boxed = Slot::<Boxed<[U256; 4]>>::new(U256::from(2));
+        boxed.write(&mut storage, values.clone())?;
+
+        assert_eq!(<Boxed<[U256; 4]>>::SLOTS, 4);
+        assert_eq!(boxed.read(&storage)?, values);
+        assert_eq!(
+            Slot::<[U256; 4]>::new(U256::from(2)).read(&storage)?,
+            *values
+        );
+
+        // packable values keep packing when boxed
+        let flags = Mapping::<U256, (Boxed<u8>, u64)>::new(U256::from(9));
+        flags
+            .at(U256::ONE)
+            .write(&mut storage, (Boxed::new(7), u64::MAX))?;
+        assert_eq!(
+            Slot::<(u8, u64)>::new(U256::ONE.mapping_slot(U256::from(9))).read(&storage)?,
+            (7, u64::MAX)
+        );
+
+        boxed.delete(&mut storage)?;
+        assert_eq!(boxed.read(&storage)?.into_inner(), [U256::ZERO; 4]);
+        Ok(())
+    }
+}
@@ -1,10 +1,11 @@
-use alloy_primitives::{Bytes, U256, keccak256};
+use alloy_primitives::{Bytes, U256};
 use std::marker::PhantomData;
 
 use crate::{
     layout::{Handler, Layout, LayoutCtx, Storable, StorableType},
+    packing::{PREALLOCATION_LIMIT, data_region_slot, decode_length},
     slot::Slot,
-    storage::StorageOps,
+    storage::{StorageOps, prefetch_range},
     InteropError,
     Result,
 };
@@ -14,7 +15,8 @@ impl StorableType for Bytes {
     const IS_DYNAMIC: bool = true;
     type Handler = BytesLikeHandler<Self>;
 
-    fn handle(slot: U256, _ctx: LayoutCtx) -> Self::Handler {
+    fn handle(slot: U256, ctx: LayoutCtx) -> Self::Handler {
+        debug_assert_eq!(ctx, LayoutCtx::FULL, "Bytes cannot be packed");
         BytesLikeHandler::new(slot)
     }
 }
@@ -24,7 +26,8 @@ impl StorableType for String {
     const IS_DYNAMIC: bool = true;
     type Handler = BytesLikeHandler<Self>;
 
-    fn handle(slot: U256, _ctx: LayoutCtx) -> Self::Handler {
+    fn handle(slot: U256, ctx: LayoutCtx) -> Self::Handler {
+        debug_assert_eq!(ctx, LayoutCtx::FULL, "String cannot be packed");
         BytesLikeHandler::new(slot)
     }
 }
@@ -44,6 +47,12 @@ impl<T: Storable> BytesLikeHandler<T> {
         }
     }
 
+    /// Equivalent to [`BytesLikeHandler::new`].
+    #[inline]
+    pub fn at_slot(slot: U256) -> Self {
+        Self::new(slot)
+    }
+
     #[inline]
     fn as_slot(&self) -> Slot<T> {
         Slot::new(self.base_slot)
@@ -53,13 +62,109 @@ impl<T: Storable> BytesLikeHandler<T> {
     pub fn len<S: StorageOps>(&self, storage: &S) -> Result<usize> {
         let base_value = Slot::<U256>::new(self.base_slot).read(storage)?;
         let is_long = is_long_string(base_value);
-        Ok(calc_string_length(base_value, is_long))
+        calc_string_length(base_value, is_long)
     }
 
     #[inline]
     pub fn is_empty<S: StorageOps>(&self, storage: &S) -> Result<bool> {
         Ok(self.len(storage)? == 0)
     }
+
+    /// Returns how many data slots the value occupies besides its header slot, reading only the
+    /// header: none for short values stored in place, `ceil(len / 32)` for long ones.
+    pub fn data_slot_count<S: StorageOps>(&self, storage: &S) -> Result<usize> {
+        let base_value = storage.load(self.base_slot)?;
+        if !is_long_string(base_value) {
+            return Ok(0);
+        }
+        Ok(calc_chunks(calc_string_length(base_value, true)?))
+    }
+
+    /// Appends `data` to the stored value, only touching the trailing partial chunk and the
+    /// newly required slots instead of rewriting the whole value.
+    ///
+    /// For `String` handlers the caller is responsible for appending valid UTF-8.
+    pub fn append<S: StorageOps>(&mut self, storage: &mut S, data: &[u8]) -> Result<()> {
+        append_bytes_like(data, storage, self.base_slot)
+    }
+
+    /// Iterates over the stored bytes in 32-byte chunks, loading one data slot per chunk instead
+    /// of buffering the whole value.
+    ///
+    /// Every chunk is 32 bytes long except the last, which is trimmed to the stored length.
+    pub fn chunks<'a, S: StorageOps>(&self, storage: &'a S) -> Result<ByteChunks<'a, S>> {
+        let header = storage.load(self.base_slot)?;
+        let is_long = is_long_string(header);
+        Ok(ByteChunks {
+            storage,
+            header,
+            data_start: data_region_slot(self.base_slot),
+            is_long,
+            len: calc_string_length(header, is_long)?,
+            index: 0,
+        })
+    }
+}
+
+/// Iterator over a stored `Bytes` or `String` in slot-sized chunks, see
+/// [`BytesLikeHandler::chunks`].
+pub struct ByteChunks<'a, S> {
+    storage: &'a S,
+    header: U256,
+    data_start: U256,
+    is_long: bool,
+    len: usize,
+    index: usize,
+}
+
+impl<S: StorageOps> Iterator for ByteChunks<'_, S> {
+    type Item = Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.index * 32;
+        if offset >= self.len {
+            return None;
+        }
+
+        // short values live in the header, which was loaded when the iterator was created
+        let word = if self.is_long {
+            match self.storage.load(self.data_start + U256::from(self.index)) {
+                Ok(word) => word,
+                Err(err) => {
+                    self.index = calc_chunks(self.len);
+                    return Some(Err(err));
+                }
+            }
+        } else {
+            self.header
+        };
+        self.index += 1;
+
+        let take = (self.len - offset).min(32);
+        Some(Ok(word.to_be_bytes::<32>()[..take].to_vec()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = calc_chunks(self.len) - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<S: StorageOps> ExactSizeIterator for ByteChunks<'_, S> {}
+
+impl BytesLikeHandler<String> {
+    /// Checks whether the stored bytes are valid UTF-8 without building a `String`.
+    pub fn is_valid_utf8<S: StorageOps>(&self, storage: &S) -> Result<bool> {
+        load_bytes_like(storage, self.base_slot, |data| {
+            Ok(std::str::from_utf8(&data).is_ok())
+        })
+    }
+}
+
+impl<T: Storable> Default for BytesLikeHandler<T> {
+    fn default() -> Self {
+        Self::new(U256::ZERO)
+    }
 }
 
 impl<T: Storable> Handler<T> for BytesLikeHandler<T> {
@@ -91,6 +196,10 @@ impl Storable for Bytes {
         debug_assert_eq!(ctx, LayoutCtx::FULL, "Bytes cannot be packed");
         delete_bytes_like(storage, slot)
     }
+
+    fn raw_slots<S: StorageOps>(storage: &S, slot: U256) -> Result<Vec<U256>> {
+        bytes_like_slots(storage, slot)
+    }
 }
 
 impl Storable for String {
@@ -110,6 +219,10 @@ impl Storable for String {
         debug_assert_eq!(ctx, LayoutCtx::FULL, "String cannot be packed");
         delete_bytes_like(storage, slot)
     }
+
+    fn raw_slots<S: StorageOps>(storage: &S, slot: U256) -> Result<Vec<U256>> {
+        bytes_like_slots(storage, slot)
+    }
 }
 
 fn load_bytes_like<T, S, F>(storage: &S, base_slot: U256, into: F) -> Result<T>
@@ -119,12 +232,13 @@ where
 {
     let base_value = storage.load(base_slot)?;
     let is_long = is_long_string(base_value);
-    let length = calc_string_length(base_value, is_long);
+    let length = calc_string_length(base_value, is_long)?;
 
     if is_long {
-        let slot_start = calc_data_slot(base_slot);
+        let slot_start = data_region_slot(base_slot);
         let chunks = calc_chunks(length);
-        let mut data = Vec::with_capacity(length);
+        prefetch_range(storage, slot_start, chunks)?;
+        let mut data = Vec::with_capacity(length.min(PREALLOCATION_LIMIT));
 
         for i in 0..chunks {
             let slot = slot_start + U256::from(i);
@@ -148,29 +262,77 @@ where
 
 fn store_bytes_like<S: StorageOps>(bytes: &[u8], storage: &mut S, base_slot: U256) -> Result<()> {
     let length = bytes.len();
+    let old_value = storage.load(base_slot)?;
+    let old_chunks = if is_long_string(old_value) {
+        calc_chunks(calc_string_length(old_value, true)?)
+    } else {
+        0
+    };
 
-    if length <= 31 {
-        storage.store(base_slot, encode_short_string(bytes))
+    let new_chunks = if length <= 31 {
+        storage.store(base_slot, encode_short_string(bytes))?;
+        0
     } else {
         storage.store(base_slot, encode_long_string_length(length))?;
+        store_chunks(bytes, storage, data_region_slot(base_slot))?;
+        calc_chunks(length)
+    };
+
+    // clear chunks of a previous, longer value so they don't linger orphaned
+    let slot_start = data_region_slot(base_slot);
+    for i in new_chunks..old_chunks {
+        storage.store(slot_start + U256::from(i), U256::ZERO)?;
+    }
+    Ok(())
+}
 
-        let slot_start = calc_data_slot(base_slot);
-        let chunks = calc_chunks(length);
+fn append_bytes_like<S: StorageOps>(data: &[u8], storage: &mut S, base_slot: U256) -> Result<()> {
+    if data.is_empty() {
+        return Ok(());
+    }
 
-        for i in 0..chunks {
-            let slot = slot_start + U256::from(i);
-            let chunk_start = i * 32;
-            let chunk_end = (chunk_start + 32).min(length);
-            let chunk = &bytes[chunk_start..chunk_end];
+    let base_value = storage.load(base_slot)?;
+    let is_long = is_long_string(base_value);
+    let length = calc_string_length(base_value, is_long)?;
+
+    if !is_long {
+        // Short values live entirely in the header slot, so re-encoding them is no more
+        // expensive than appending and also covers the short -> long transition.
+        let mut bytes = base_value.to_be_bytes::<32>()[..length].to_vec();
+        bytes.extend_from_slice(data);
+        return store_bytes_like(&bytes, storage, base_slot);
+    }
 
-            let mut chunk_bytes = [0u8; 32];
-            chunk_bytes[..chunk.len()].copy_from_slice(chunk);
+    let slot_start = data_region_slot(base_slot);
+    let used = length % 32;
+    let mut written = 0;
 
-            storage.store(slot, U256::from_be_bytes(chunk_bytes))?;
-        }
+    if used != 0 {
+        let slot = slot_start + U256::from(length / 32);
+        written = (32 - used).min(data.len());
 
-        Ok(())
+        let mut chunk_bytes = storage.load(slot)?.to_be_bytes::<32>();
+        chunk_bytes[used..used + written].copy_from_slice(&data[..written]);
+        storage.store(slot, U256::from_be_bytes(chunk_bytes))?;
     }
+
+    store_chunks(
+        &data[written..],
+        storage,
+        slot_start + U256::from(calc_chunks(length)),
+    )?;
+    storage.store(base_slot, encode_long_string_length(length + data.len()))
+}
+
+fn store_chunks<S: StorageOps>(bytes: &[u8], storage: &mut S, slot_start: U256) -> Result<()> {
+    for (i, chunk) in bytes.chunks(32).enumerate() {
+        let mut chunk_bytes = [0u8; 32];
+        chunk_bytes[..chunk.len()].copy_from_slice(chunk);
+
+        storage.store(slot_start + U256::from(i), U256::from_be_bytes(chunk_bytes))?;
+    }
+
+    Ok(())
 }
 
 fn delete_bytes_like<S: StorageOps>(storage: &mut S, base_slot: U256) -> Result<()> {
@@ -178,8 +340,8 @@ fn delete_bytes_like<S: StorageOps>(storage: &mut S, base_slot: U256) -> Result<
     let is_long = is_long_string(base_value);
 
     if is_long {
-        let length = calc_string_length(base_value, true);
-        let slot_start = calc_data_slot(base_slot);
+        let length = calc_string_length(base_value, true)?;
+        let slot_start = data_region_slot(base_slot);
         let chunks = calc_chunks(length);
 
         for i in 0..chunks {
@@ -191,9 +353,17 @@ fn delete_bytes_like<S: StorageOps>(storage: &mut S, base_slot: U256) -> Result<
     storage.store(base_slot, U256::ZERO)
 }
 
-#[inline]
-fn calc_data_slot(base_slot: U256) -> U256 {
-    U256::from_be_bytes(keccak256(base_slot.to_be_bytes::<32>()).0)
+fn bytes_like_slots<S: StorageOps>(storage: &S, base_slot: U256) -> Result<Vec<U256>> {
+    let base_value = storage.load(base_slot)?;
+    let mut slots = vec![base_slot];
+
+    if is_long_string(base_value) {
+        let slot_start = data_region_slot(base_slot);
+        let chunks = calc_chunks(calc_string_length(base_value, true)?);
+        slots.extend((0..chunks).map(|i| slot_start + U256::from(i)));
+    }
+
+    Ok(slots)
 }
 
 #[inline]
@@ -207,11 +377,11 @@ fn is_long_string(value: U256) -> bool {
 }
 
 #[inline]
-fn calc_string_length(value: U256, is_long: bool) -> usize {
+fn calc_string_length(value: U256, is_long: bool) -> Result<usize> {
     if is_long {
-        (value >> 1).to::<usize>()
+        decode_length(value >> 1)
     } else {
-        ((value & U256::from(0xff)) >> 1).to::<usize>()
+        Ok(((value & U256::from(0xff)) >> 1).to::<usize>())
     }
 }
 
@@ -227,3 +397,164 @@ fn encode_short_string(bytes: &[u8]) -> U256 {
 fn encode_long_string_length(length: usize) -> U256 {
     U256::from((length as u64) << 1 | 1)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        packing::MAX_DYNAMIC_LENGTH,
+        test_util::{CountingStorage, MemoryStorage},
+    };
+
+    #[test]
+    fn test_data_slot_count() -> Result<()> {
+        let mut storage = MemoryStorage::new();
+        let mut handler = BytesLikeHandler::<Bytes>::new(U256::from(3));
+        assert_eq!(handler.data_slot_count(&storage)?, 0);
+
+        handler.write(&mut storage, Bytes::from(vec![1; 31]))?;
+        assert_eq!(handler.data_slot_count(&storage)?, 0);
+
+        handler.write(&mut storage, Bytes::from(vec![1; 32]))?;
+        assert_eq!(handler.data_slot_count(&storage)?, 1);
+
+        handler.write(&mut storage, Bytes::from(vec![1; 65]))?;
+        assert_eq!(handler.data_slot_count(&storage)?, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_append_matches_one_shot_store() -> Result<()> {
+        let data: Vec<u8> = (0..=255u8).cycle().take(150).collect();
+
+        let mut appended = MemoryStorage::default();
+        let mut handler = BytesLikeHandler::<Bytes>::new(U256::from(3));
+        for piece in data.chunks(7) {
+            handler.append(&mut appended, piece)?;
+        }
+
+        let mut one_shot = MemoryStorage::default();
+        BytesLikeHandler::<Bytes>::new(U256::from(3))
+            .write(&mut one_shot, Bytes::from(data.clone()))?;
+
+        assert_eq!(appended, one_shot);
+        assert_eq!(handler.read(&appended)?, Bytes::from(data));
+        Ok(())
+    }
+
+    #[test]
+    fn test_append_short_to_long_transition() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+        let mut handler = BytesLikeHandler::<String>::new(U256::ZERO);
+
+        handler.write(&mut storage, "a".repeat(30))?;
+        handler.append(&mut storage, b"bb")?;
+        assert_eq!(handler.len(&storage)?, 32);
+
+        handler.append(&mut storage, b"c")?;
+        assert_eq!(handler.read(&storage)?, format!("{}bbc", "a".repeat(30)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_valid_utf8() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+        let mut handler = BytesLikeHandler::<String>::new(U256::ONE);
+
+        handler.write(&mut storage, "héllo".repeat(10))?;
+        assert!(handler.is_valid_utf8(&storage)?);
+
+        BytesLikeHandler::<Bytes>::new(U256::ONE)
+            .write(&mut storage, Bytes::from_static(&[b'o', b'k', 0xff, 0xfe]))?;
+        assert!(!handler.is_valid_utf8(&storage)?);
+        assert!(matches!(
+            handler.read(&storage),
+            Err(InteropError::InvalidUtf8)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_shrinking_store_clears_old_chunks() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+        let mut handler = BytesLikeHandler::<String>::new(U256::from(8));
+        handler.write(&mut storage, "x".repeat(200))?;
+
+        let data_start = data_region_slot(U256::from(8));
+        assert_ne!(storage.load(data_start + U256::from(6))?, U256::ZERO);
+
+        handler.write(&mut storage, "short".to_string())?;
+        for i in 0..7 {
+            assert_eq!(storage.load(data_start + U256::from(i))?, U256::ZERO);
+        }
+        assert_eq!(handler.read(&storage)?, "short");
+
+        // long -> shorter long keeps only the chunks still in use
+        handler.write(&mut storage, "x".repeat(200))?;
+        handler.write(&mut storage, "y".repeat(40))?;
+        let mut fresh = MemoryStorage::default();
+        BytesLikeHandler::<String>::new(U256::from(8)).write(&mut fresh, "y".repeat(40))?;
+        assert_eq!(storage, fresh);
+        Ok(())
+    }
+
+    #[test]
+    fn test_chunks_match_full_read() -> Result<()> {
+        let mut storage = CountingStorage::default();
+        let blob: Vec<u8> = (0..1024u32).map(|i| (i * 7 % 251) as u8).collect();
+        let mut handler = BytesLikeHandler::<Bytes>::new(U256::from(3));
+        handler.write(&mut storage, Bytes::from(blob.clone()))?;
+
+        storage.reset();
+        let chunks = handler.chunks(&storage)?;
+        assert_eq!(chunks.len(), 32);
+        let (mut total, mut streamed) = (0u64, Vec::new());
+        for chunk in chunks {
+            let chunk = chunk?;
+            total += chunk.iter().map(|&b| b as u64).sum::<u64>();
+            streamed.extend(chunk);
+        }
+        assert_eq!(total, blob.iter().map(|&b| b as u64).sum::<u64>());
+        assert_eq!(streamed, handler.read(&storage)?);
+        assert_eq!(storage.loads(), 1 + 32 + 1 + 32);
+
+        // trailing partial chunk and short values
+        handler.append(&mut storage, &[9; 5])?;
+        let last = handler.chunks(&storage)?.last().unwrap()?;
+        assert_eq!(last, [9; 5]);
+
+        handler.write(&mut storage, Bytes::from_static(b"short"))?;
+        let chunks: Vec<_> = handler.chunks(&storage)?.collect::<Result<_>>()?;
+        assert_eq!(chunks, [b"short".to_vec()]);
+
+        handler.write(&mut storage, Bytes::new())?;
+        assert_eq!(handler.chunks(&storage)?.count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rejects_implausible_long_header() -> Result<()> {
+        let mut storage = MemoryStorage::new();
+        storage.store(U256::ONE, U256::MAX)?;
+        let handler = BytesLikeHandler::<Bytes>::new(U256::ONE);
+
+        for result in [
+            handler.len(&storage).map(drop),
+            handler.read(&storage).map(drop),
+            handler.chunks(&storage).map(drop),
+            Bytes::raw_slots(&storage, U256::ONE).map(drop),
+        ] {
+            assert!(matches!(
+                result,
+                Err(InteropError::InvalidLength { claimed, max: MAX_DYNAMIC_LENGTH })
+                    if claimed == U256::MAX >> 1
+            ));
+        }
+
+        // the largest accepted length still decodes
+        let longest = U256::from(MAX_DYNAMIC_LENGTH);
+        storage.store(U256::ONE, (longest << 1) | U256::ONE)?;
+        assert_eq!(handler.len(&storage)?, MAX_DYNAMIC_LENGTH);
+        Ok(())
+    }
+}
@@ -0,0 +1,462 @@
+//! Runtime description of storage layouts for tooling.
+//!
+//! [`DescribeLayout::storage_layout`] returns a tree mirroring the `storage` section of
+//! `solc --storage-layout`: every node carries a Solidity type label, its in-place size and how
+//! its data is located. With the `serde` feature the tree serializes to JSON, so a layout
+//! declared in Rust can be diffed against the compiler's output.
+
+use alloy_primitives::{Address, Bytes, U256, aliases::*};
+use std::collections::BTreeMap;
+
+use crate::{
+    bitvec::BitVec,
+    enumerable::EnumerableMapping,
+    layout::{Layout, Storable, StorableType},
+    mapping::Mapping,
+    packing::calc_field_locs,
+    storage::StorageKey,
+};
+
+/// How a value is located relative to its slot, named after solc's `encoding` values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Encoding {
+    /// Stored in place, starting at the slot (and byte offset) of the value.
+    Inplace,
+    /// The slot stays empty; the value for `key` lives at `keccak256(key . slot)`.
+    Mapping,
+    /// The slot holds the length; elements start at `keccak256(slot)`.
+    DynamicArray,
+    /// Short values share the slot with their length; long ones start at `keccak256(slot)`.
+    Bytes,
+}
+
+/// Layout of a type, relative to the slot it is stored at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct StorageLayout {
+    /// Solidity type label, e.g. `uint8`, `address[]` or `mapping(address => uint256)`.
+    pub type_name: String,
+    pub encoding: Encoding,
+    /// Bytes occupied in place. Dynamic types only account for their header slot.
+    pub size: usize,
+    /// Struct or tuple fields, in declaration order.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub members: Vec<FieldDescription>,
+    /// Mapping key type.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub key: Option<Box<StorageLayout>>,
+    /// Mapping value or array element type, laid out in the hashed data region for dynamic
+    /// encodings.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub value: Option<Box<StorageLayout>>,
+}
+
+impl StorageLayout {
+    /// Layout of a value of `T` stored in place.
+    pub fn inplace<T: StorableType>(type_name: impl Into<String>) -> Self {
+        Self::with_encoding(type_name, Encoding::Inplace, T::LAYOUT.bytes())
+    }
+
+    /// Layout of a dynamic value whose in-place part is the single header slot.
+    pub fn dynamic(type_name: impl Into<String>, encoding: Encoding) -> Self {
+        Self::with_encoding(type_name, encoding, 32)
+    }
+
+    /// Layout of a struct of `members`, occupying `slots` full slots.
+    pub fn structure(
+        type_name: impl Into<String>,
+        slots: usize,
+        members: Vec<FieldDescription>,
+    ) -> Self {
+        Self {
+            members,
+            ..Self::with_encoding(type_name, Encoding::Inplace, slots * 32)
+        }
+    }
+
+    fn with_encoding(type_name: impl Into<String>, encoding: Encoding, size: usize) -> Self {
+        Self {
+            type_name: type_name.into(),
+            encoding,
+            size,
+            members: Vec::new(),
+            key: None,
+            value: None,
+        }
+    }
+
+    fn with_key(mut self, key: Self) -> Self {
+        self.key = Some(Box::new(key));
+        self
+    }
+
+    fn with_value(mut self, value: Self) -> Self {
+        self.value = Some(Box::new(value));
+        self
+    }
+}
+
+/// A struct field, located relative to the struct's base slot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FieldDescription {
+    pub field_name: String,
+    pub slot_offset: usize,
+    pub byte_offset: usize,
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub layout: StorageLayout,
+}
+
+/// Types that can describe their own storage layout.
+///
+/// Implemented for all built-in storable types, for enums declared with
+/// [`impl_packable_enum!`](crate::impl_packable_enum), and for structs declared with
+/// [`storable_struct!`](crate::storable_struct) that start with `#[describe]`.
+pub trait DescribeLayout: StorableType {
+    fn storage_layout() -> StorageLayout;
+}
+
+/// Describes `fields` with the same packing rules used to store them.
+#[doc(hidden)]
+pub fn describe_fields<const N: usize>(
+    names: [&str; N],
+    layouts: [(Layout, StorageLayout); N],
+) -> Vec<FieldDescription> {
+    let locs = calc_field_locs(layouts.each_ref().map(|(layout, _)| *layout));
+    names
+        .into_iter()
+        .zip(layouts)
+        .zip(locs)
+        .map(|((name, (_, layout)), loc)| FieldDescription {
+            field_name: name.to_string(),
+            slot_offset: loc.offset_slots,
+            byte_offset: loc.offset_bytes,
+            layout,
+        })
+        .collect()
+}
+
+macro_rules! describe_inplace {
+    ($($ty:ty => $name:expr),+ $(,)?) => {$(
+        impl DescribeLayout for $ty {
+            fn storage_layout() -> StorageLayout {
+                StorageLayout::inplace::<Self>($name)
+            }
+        }
+    )+};
+}
+
+macro_rules! describe_uint_aliases {
+    ($($ty:ty),+ $(,)?) => {$(
+        impl DescribeLayout for $ty {
+            fn storage_layout() -> StorageLayout {
+                StorageLayout::inplace::<Self>(format!("uint{}", <$ty>::BITS))
+            }
+        }
+    )+};
+}
+
+describe_inplace!(
+    bool => "bool",
+    char => "uint32",
+    Address => "address",
+    u8 => "uint8",
+    u16 => "uint16",
+    u32 => "uint32",
+    u64 => "uint64",
+    u128 => "uint128",
+    i8 => "int8",
+    i16 => "int16",
+    i32 => "int32",
+    i64 => "int64",
+    i128 => "int128",
+    U256 => "uint256",
+);
+
+/// Solidity has no 512-bit integer. The two words are stored high word first, exactly like a
+/// `uint256[2]`, so that is how the layout is described.
+impl DescribeLayout for U512 {
+    fn storage_layout() -> StorageLayout {
+        <[U256; 2]>::storage_layout()
+    }
+}
+
+describe_uint_aliases!(
+    U8, U16, U24, U32, U40, U48, U56, U64, U72, U80, U88, U96, U104, U112, U120, U128, U136, U144,
+    U152, U160, U168, U176, U184, U192, U200, U208, U216, U224, U232, U240, U248,
+);
+
+impl DescribeLayout for String {
+    fn storage_layout() -> StorageLayout {
+        StorageLayout::dynamic("string", Encoding::Bytes)
+    }
+}
+
+impl DescribeLayout for Bytes {
+    fn storage_layout() -> StorageLayout {
+        StorageLayout::dynamic("bytes", Encoding::Bytes)
+    }
+}
+
+/// Not a Solidity type: bits are packed 256 per slot rather than one byte per `bool`.
+impl DescribeLayout for BitVec {
+    fn storage_layout() -> StorageLayout {
+        StorageLayout::dynamic("bitvec", Encoding::DynamicArray)
+            .with_value(StorageLayout::inplace::<bool>("bool"))
+    }
+}
+
+impl<T> DescribeLayout for Vec<T>
+where
+    T: DescribeLayout + Storable,
+{
+    fn storage_layout() -> StorageLayout {
+        let element = T::storage_layout();
+        StorageLayout::dynamic(format!("{}[]", element.type_name), Encoding::DynamicArray)
+            .with_value(element)
+    }
+}
+
+impl<T, const N: usize> DescribeLayout for [T; N]
+where
+    T: DescribeLayout + Storable,
+{
+    fn storage_layout() -> StorageLayout {
+        let element = T::storage_layout();
+        StorageLayout::inplace::<Self>(format!("{}[{N}]", element.type_name)).with_value(element)
+    }
+}
+
+impl<K, V> DescribeLayout for Mapping<K, V>
+where
+    K: DescribeLayout,
+    V: DescribeLayout,
+{
+    fn storage_layout() -> StorageLayout {
+        let (key, value) = (K::storage_layout(), V::storage_layout());
+        let type_name = format!("mapping({} => {})", key.type_name, value.type_name);
+        StorageLayout::dynamic(type_name, Encoding::Mapping)
+            .with_key(key)
+            .with_value(value)
+    }
+}
+
+/// Described as OpenZeppelin's `EnumerableMap` struct of a key array and two mappings.
+impl<K, V> DescribeLayout for EnumerableMapping<K, V>
+where
+    K: StorageKey + DescribeLayout + Storable,
+    V: DescribeLayout + Storable,
+{
+    fn storage_layout() -> StorageLayout {
+        let members = describe_fields(
+            ["_keys", "_positions", "_values"],
+            [
+                (Layout::Slots(1), Vec::<K>::storage_layout()),
+                (Layout::Slots(1), Mapping::<K, U256>::storage_layout()),
+                (Layout::Slots(1), Mapping::<K, V>::storage_layout()),
+            ],
+        );
+        let type_name = format!(
+            "EnumerableMap({} => {})",
+            K::storage_layout().type_name,
+            V::storage_layout().type_name
+        );
+        StorageLayout::structure(type_name, Self::SLOTS, members)
+    }
+}
+
+/// Not a Solidity type: described as the key-ordered `(K, V)[]` the entries are stored as.
+impl<K, V> DescribeLayout for BTreeMap<K, V>
+where
+    K: DescribeLayout + Storable + Ord,
+    V: DescribeLayout + Storable,
+{
+    fn storage_layout() -> StorageLayout {
+        Vec::<(K, V)>::storage_layout()
+    }
+}
+
+macro_rules! describe_tuple {
+    ($($idx:tt => $ty:ident),+) => {
+        impl<$($ty: DescribeLayout + Storable),+> DescribeLayout for ($($ty,)+) {
+            fn storage_layout() -> StorageLayout {
+                let members = describe_fields(
+                    [$(stringify!($idx)),+],
+                    [$(($ty::LAYOUT, $ty::storage_layout())),+],
+                );
+                let names: Vec<_> = members.iter().map(|m| m.layout.type_name.as_str()).collect();
+                let type_name = format!("({})", names.join(", "));
+                StorageLayout::structure(type_name, Self::SLOTS, members)
+            }
+        }
+    };
+}
+
+describe_tuple!(0 => A, 1 => B);
+describe_tuple!(0 => A, 1 => B, 2 => C);
+describe_tuple!(0 => A, 1 => B, 2 => C, 3 => D);
+describe_tuple!(0 => A, 1 => B, 2 => C, 3 => D, 4 => E);
+describe_tuple!(0 => A, 1 => B, 2 => C, 3 => D, 4 => E, 5 => F);
+describe_tuple!(0 => A, 1 => B, 2 => C, 3 => D, 4 => E, 5 => F, 6 => G);
+describe_tuple!(0 => A, 1 => B, 2 => C, 3 => D, 4 => E, 5 => F, 6 => G, 7 => H);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Boxed;
+
+    crate::storable_struct! {
+        #[describe]
+        struct Policy {
+            policy_type: u8,
+            admin: Address,
+            limit: U256,
+            members: Vec<Address>,
+        }
+    }
+
+    #[test]
+    fn test_describe_struct_fields() {
+        let layout = Policy::storage_layout();
+        assert_eq!(layout.type_name, "struct Policy");
+        assert_eq!(layout.size, 3 * 32);
+
+        let fields: Vec<_> = layout
+            .members
+            .iter()
+            .map(|f| {
+                (
+                    f.field_name.as_str(),
+                    f.slot_offset,
+                    f.byte_offset,
+                    f.layout.size,
+                )
+            })
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                ("policy_type", 0, 0, 1),
+                ("admin", 0, 1, 20),
+                ("limit", 1, 0, 32),
+                ("members", 2, 0, 32),
+            ]
+        );
+        let type_names: Vec<_> = layout.members.iter().map(|f| &f.layout.type_name).collect();
+        assert_eq!(type_names, ["uint8", "address", "uint256", "address[]"]);
+        assert_eq!(layout.members[3].layout.encoding, Encoding::DynamicArray);
+
+        let mapping = Mapping::<Address, [u16; 3]>::storage_layout();
+        assert_eq!(mapping.type_name, "mapping(address => uint16[3])");
+        assert_eq!(mapping.value.unwrap().size, 32);
+        assert_eq!(<(bool, U96)>::storage_layout().type_name, "(bool, uint96)");
+
+        let wide = U512::storage_layout();
+        assert_eq!((wide.type_name.as_str(), wide.size), ("uint256[2]", 64));
+    }
+
+    #[test]
+    fn test_describe_struct_with_ordered_map() {
+        crate::storable_struct! {
+            #[describe]
+            struct Snapshot {
+                owner: Address,
+                balances: BTreeMap<Address, U256>,
+            }
+        }
+
+        let layout = Snapshot::storage_layout();
+        let balances = &layout.members[1];
+        assert_eq!(
+            (balances.field_name.as_str(), balances.slot_offset),
+            ("balances", 1)
+        );
+        assert_eq!(balances.layout.type_name, "(address, uint256)[]");
+        assert_eq!(balances.layout.encoding, Encoding::DynamicArray);
+        assert_eq!(balances.layout.value.as_ref().unwrap().size, 64);
+    }
+
+    #[test]
+    fn test_every_storable_type_describes_its_layout() {
+        crate::storable_struct! {
+            #[describe]
+            struct Everything {
+                flag: bool,
+                letter: char,
+                owner: Address,
+                unsigned: (u8, u16, u32, u64, u128),
+                signed: (i8, i16, i32, i64, i128),
+                word: U256,
+                wide: U512,
+                text: String,
+                data: Bytes,
+                bits: BitVec,
+                list: Vec<u64>,
+                fixed: [u16; 3],
+                nested: (bool, u8, u16, u32, u64, u128, Address, U256),
+                boxed: Boxed<U256>,
+                ordered: BTreeMap<U256, Address>,
+            }
+        }
+
+        let layout = Everything::storage_layout();
+        assert_eq!(layout.members.len(), 15);
+        assert_eq!(layout.size, Everything::SLOTS * 32);
+
+        macro_rules! assert_uint_aliases {
+            ($($ty:ty),+ $(,)?) => {$(
+                assert_eq!(<$ty>::storage_layout().type_name, format!("uint{}", <$ty>::BITS));
+            )+};
+        }
+        assert_uint_aliases!(
+            U8, U16, U24, U32, U40, U48, U56, U64, U72, U80, U88, U96, U104, U112, U120, U128,
+            U136, U144, U152, U160, U168, U176, U184, U192, U200, U208, U216, U224, U232, U240,
+            U248,
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serialize_packed_struct() {
+        crate::storable_struct! {
+            #[describe]
+            struct Packed {
+                paused: bool,
+                owner: Address,
+            }
+        }
+
+        let json = serde_json::to_value(Packed::storage_layout()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "type_name": "struct Packed",
+                "encoding": "inplace",
+                "size": 32,
+                "members": [
+                    {
+                        "field_name": "paused",
+                        "slot_offset": 0,
+                        "byte_offset": 0,
+                        "type_name": "bool",
+                        "encoding": "inplace",
+                        "size": 1
+                    },
+                    {
+                        "field_name": "owner",
+                        "slot_offset": 0,
+                        "byte_offset": 1,
+                        "type_name": "address",
+                        "encoding": "inplace",
+                        "size": 20
+                    }
+                ]
+            })
+        );
+    }
+}
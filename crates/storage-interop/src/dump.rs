@@ -0,0 +1,204 @@
+//! [`StorageOps`] backed by a storage dump, for decoding real contract state with the crate's
+//! handlers.
+//!
+//! Dumps are flat JSON objects mapping hex slots to hex values, the shape produced by
+//! collecting `eth_getStorageAt` results:
+//!
+//! ```json
+//! { "0x0": "0x000000000000000000000000000000000000000000000000000000000000002a" }
+//! ```
+//!
+//! Parsing dumps requires the `serde` feature.
+
+use alloy_primitives::U256;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{Result, storage::StorageOps};
+
+/// Read-only view of a storage dump.
+///
+/// Stores never modify the dump: they are buffered in an overlay that later loads see, so
+/// handlers performing read-modify-write still behave, and [`DumpStorage::writes`] reports them.
+#[derive(Debug, Clone, Default)]
+pub struct DumpStorage {
+    slots: HashMap<U256, U256>,
+    writes: BTreeMap<U256, U256>,
+}
+
+impl DumpStorage {
+    pub fn new(slots: HashMap<U256, U256>) -> Self {
+        Self {
+            slots,
+            writes: BTreeMap::new(),
+        }
+    }
+
+    /// Parses a `{ "0x<slot>": "0x<value>" }` dump. Slots given more than once are rejected,
+    /// also when spelled differently such as `0x1` and `0x01`.
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self> {
+        let DumpSlots(slots) =
+            serde_json::from_str(json).map_err(|err| invalid_dump(err.to_string()))?;
+        Ok(Self::new(slots))
+    }
+
+    /// Reads and parses a dump file, see [`Self::from_json`].
+    #[cfg(feature = "serde")]
+    pub fn from_json_file(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .map_err(|err| invalid_dump(format!("{}: {err}", path.display())))?;
+        Self::from_json(&json)
+    }
+
+    /// Serializes the original dump, without buffered writes, sorted by slot.
+    pub fn to_json(&self) -> String {
+        let mut entries: Vec<_> = self.slots.iter().collect();
+        entries.sort();
+        let entries: Vec<_> = entries
+            .into_iter()
+            .map(|(slot, value)| format!("  \"{slot:#x}\": \"{value:#066x}\""))
+            .collect();
+        format!("{{\n{}\n}}", entries.join(",\n"))
+    }
+
+    /// Returns the buffered `(slot, value)` writes, sorted by slot.
+    pub fn writes(&self) -> Vec<(U256, U256)> {
+        self.writes
+            .iter()
+            .map(|(slot, value)| (*slot, *value))
+            .collect()
+    }
+}
+
+impl StorageOps for DumpStorage {
+    fn load(&self, slot: U256) -> Result<U256> {
+        let value = self.writes.get(&slot).or_else(|| self.slots.get(&slot));
+        Ok(value.copied().unwrap_or_default())
+    }
+
+    fn store(&mut self, slot: U256, value: U256) -> Result<()> {
+        self.writes.insert(slot, value);
+        Ok(())
+    }
+}
+
+/// The slots of a dump, deserialized without letting a repeated slot overwrite an earlier one.
+#[cfg(feature = "serde")]
+struct DumpSlots(HashMap<U256, U256>);
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for DumpSlots {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        struct SlotsVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for SlotsVisitor {
+            type Value = DumpSlots;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("a JSON object mapping hex slots to hex values")
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> std::result::Result<DumpSlots, A::Error> {
+                use serde::de::Error;
+
+                let mut slots = HashMap::with_capacity(map.size_hint().unwrap_or(0));
+                while let Some((slot, value)) = map.next_entry::<String, String>()? {
+                    let slot = parse_word(&slot).map_err(A::Error::custom)?;
+                    let value = parse_word(&value).map_err(A::Error::custom)?;
+                    if slots.insert(slot, value).is_some() {
+                        return Err(A::Error::custom(format!("duplicate slot {slot:#x}")));
+                    }
+                }
+                Ok(DumpSlots(slots))
+            }
+        }
+
+        deserializer.deserialize_map(SlotsVisitor)
+    }
+}
+
+/// Parses a `0x`/`0X` prefixed hex word.
+#[cfg(feature = "serde")]
+fn parse_word(word: &str) -> std::result::Result<U256, String> {
+    let hex = word
+        .strip_prefix("0x")
+        .or_else(|| word.strip_prefix("0X"))
+        .filter(|hex| !hex.is_empty())
+        .ok_or_else(|| format!("expected a 0x prefixed hex word, got `{word}`"))?;
+    U256::from_str_radix(hex, 16).map_err(|err| format!("`{word}`: {err}"))
+}
+
+#[cfg(feature = "serde")]
+fn invalid_dump(reason: impl Into<String>) -> crate::InteropError {
+    crate::InteropError::InvalidStorageDump(reason.into())
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use crate::{Handler, InteropError, Mapping, Slot, StorageKey};
+    use alloy_primitives::Address;
+
+    #[test]
+    fn test_decode_and_round_trip_dump() -> Result<()> {
+        let holder = Address::repeat_byte(0x11);
+        let balance_slot = holder.mapping_slot(U256::ONE);
+        let json = format!(
+            r#"{{
+                "0x0": "0x000000000000000000000000000000000000000000000000000000000000002a",
+                "{balance_slot:#x}": "0x3e8"
+            }}"#
+        );
+
+        let mut storage = DumpStorage::from_json(&json)?;
+        assert_eq!(
+            Slot::<U256>::new(U256::ZERO).read(&storage)?,
+            U256::from(42)
+        );
+        let balances = Mapping::<Address, U256>::new(U256::ONE);
+        assert_eq!(balances.at(holder).read(&storage)?, U256::from(1000));
+
+        let reparsed = DumpStorage::from_json(&storage.to_json())?;
+        assert_eq!(reparsed.slots, storage.slots);
+
+        // writes are buffered without touching the dump
+        balances.at(holder).write(&mut storage, U256::ZERO)?;
+        assert_eq!(balances.at(holder).read(&storage)?, U256::ZERO);
+        assert_eq!(storage.writes(), [(balance_slot, U256::ZERO)]);
+        assert_eq!(storage.slots[&balance_slot], U256::from(1000));
+        Ok(())
+    }
+
+    #[test]
+    fn test_rejects_malformed_dump() {
+        for json in [
+            "[]",
+            r#""0x1""#,
+            r#"{ "0x1" }"#,
+            r#"{ "0x1": "zz" }"#,
+            r#"{ "0x1": 1 }"#,
+            r#"{ "1": "0x1" }"#,
+            r#"{ "0x": "0x1" }"#,
+            r#"{ "0x1": "0x1" } trailing"#,
+            r#"{ "0x1": "0x1", "0x01": "0x2" }"#,
+        ] {
+            assert!(matches!(
+                DumpStorage::from_json(json),
+                Err(InteropError::InvalidStorageDump(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_accepts_upper_case_prefix() -> Result<()> {
+        let storage = DumpStorage::from_json(r#"{ "0X2": "0XFF" }"#)?;
+        assert_eq!(storage.load(U256::from(2))?, U256::from(0xff));
+        Ok(())
+    }
+}
@@ -0,0 +1,282 @@
+//! Enumerable mapping modelled on OpenZeppelin's `EnumerableMap`.
+//!
+//! The layout follows OZ's `struct { Bytes32Set _keys; mapping(bytes32 => bytes32) _values; }`,
+//! where the `_keys` set is laid out as
+//! `struct { bytes32[] _values; mapping(bytes32 => uint256) _positions; }`. The key array lives
+//! at `base_slot`, the positions mapping at `base_slot + 1` and the values mapping at
+//! `base_slot + 2`. Positions are stored one-based so that `0` unambiguously means "absent",
+//! independently of the stored value.
+//!
+//! The key array is a plain `K[]`, so keys of 16 bytes or less are packed several per slot
+//! rather than taking a `bytes32` each. The storage is only identical to OZ's for wider keys
+//! such as `Address`, `U256` and `B256`, which covers the key types OZ provides.
+
+use alloy_primitives::U256;
+use std::marker::PhantomData;
+
+use crate::{
+    InteropError, Result,
+    layout::{Handler, Layout, LayoutCtx, Storable, StorableType},
+    storage::{StorageKey, StorageOps},
+    vec::VecHandler,
+};
+
+#[derive(Debug, Clone)]
+pub struct EnumerableMapping<K, V> {
+    base_slot: U256,
+    _phantom: PhantomData<(K, V)>,
+}
+
+impl<K, V> Default for EnumerableMapping<K, V>
+where
+    K: StorageKey + Storable,
+    V: Storable,
+{
+    fn default() -> Self {
+        Self::new(U256::ZERO)
+    }
+}
+
+impl<K, V> EnumerableMapping<K, V>
+where
+    K: StorageKey + Storable,
+    V: Storable,
+{
+    #[inline]
+    pub fn new(base_slot: U256) -> Self {
+        Self {
+            base_slot,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Equivalent to [`EnumerableMapping::new`].
+    #[inline]
+    pub fn at_slot(slot: U256) -> Self {
+        Self::new(slot)
+    }
+
+    #[inline]
+    pub const fn slot(&self) -> U256 {
+        self.base_slot
+    }
+
+    #[inline]
+    fn key_vec(&self) -> VecHandler<K> {
+        VecHandler::new(self.base_slot)
+    }
+
+    #[inline]
+    fn position_slot(&self, key: &K) -> U256 {
+        key.mapping_slot(self.base_slot + U256::ONE)
+    }
+
+    #[inline]
+    fn value_slot(&self, key: &K) -> U256 {
+        key.mapping_slot(self.base_slot + U256::from(2))
+    }
+
+    #[inline]
+    pub fn len<S: StorageOps>(&self, storage: &S) -> Result<usize> {
+        self.key_vec().len(storage)
+    }
+
+    #[inline]
+    pub fn is_empty<S: StorageOps>(&self, storage: &S) -> Result<bool> {
+        Ok(self.len(storage)? == 0)
+    }
+
+    /// Returns all keys in key array order: insertion order, except that `remove` moves the last
+    /// key into the removed key's position.
+    #[inline]
+    pub fn keys<S: StorageOps>(&self, storage: &S) -> Result<Vec<K>> {
+        self.key_vec().read(storage)
+    }
+
+    #[inline]
+    pub fn contains<S: StorageOps>(&self, storage: &S, key: &K) -> Result<bool> {
+        Ok(!storage.load(self.position_slot(key))?.is_zero())
+    }
+
+    pub fn get<S: StorageOps>(&self, storage: &S, key: &K) -> Result<Option<V>> {
+        if !self.contains(storage, key)? {
+            return Ok(None);
+        }
+
+        V::load(storage, self.value_slot(key), LayoutCtx::FULL).map(Some)
+    }
+
+    /// Inserts or updates `key`. Returns `true` if the key was not present before.
+    pub fn insert<S: StorageOps>(&mut self, storage: &mut S, key: K, value: V) -> Result<bool> {
+        value.store(storage, self.value_slot(&key), LayoutCtx::FULL)?;
+
+        if self.contains(storage, &key)? {
+            return Ok(false);
+        }
+
+        let keys = self.key_vec();
+        let len = keys.len(storage)?;
+        let (slot, ctx) = keys.element_location(len);
+        key.store(storage, slot, ctx)?;
+        storage.store(keys.len_slot(), U256::from(len + 1))?;
+        storage.store(self.position_slot(&key), U256::from(len + 1))?;
+
+        Ok(true)
+    }
+
+    /// Removes `key` using swap-and-pop on the key array. Returns `true` if the key was present.
+    pub fn remove<S: StorageOps>(&mut self, storage: &mut S, key: &K) -> Result<bool> {
+        let position_slot = self.position_slot(key);
+        let position = storage.load(position_slot)?;
+        if position.is_zero() {
+            return Ok(false);
+        }
+
+        let keys = self.key_vec();
+        let len = keys.len(storage)?;
+        let invalid = || InteropError::InvalidPosition { position, len };
+        let index = usize::try_from(position)
+            .ok()
+            .and_then(|position| position.checked_sub(1))
+            .ok_or_else(invalid)?;
+        let last_index = len
+            .checked_sub(1)
+            .filter(|last_index| index <= *last_index)
+            .ok_or_else(invalid)?;
+        let (last_slot, last_ctx) = keys.element_location(last_index);
+
+        if index != last_index {
+            let last_key = K::load(storage, last_slot, last_ctx)?;
+            let (slot, ctx) = keys.element_location(index);
+            last_key.store(storage, slot, ctx)?;
+            storage.store(self.position_slot(&last_key), position)?;
+        }
+
+        K::delete(storage, last_slot, last_ctx)?;
+        storage.store(keys.len_slot(), U256::from(last_index))?;
+        storage.store(position_slot, U256::ZERO)?;
+        V::delete(storage, self.value_slot(key), LayoutCtx::FULL)?;
+
+        Ok(true)
+    }
+}
+
+impl<K, V> StorableType for EnumerableMapping<K, V>
+where
+    K: StorageKey + Storable,
+    V: Storable,
+{
+    const LAYOUT: Layout = Layout::Slots(3);
+    type Handler = Self;
+
+    fn handle(slot: U256, ctx: LayoutCtx) -> Self::Handler {
+        debug_assert_eq!(ctx, LayoutCtx::FULL, "Mappings cannot be packed");
+        Self::new(slot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::MemoryStorage;
+    use alloy_primitives::Address;
+
+    #[test]
+    fn test_remove_zero_value_key() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+        let mut map = EnumerableMapping::<Address, U256>::new(U256::from(5));
+        let key = Address::repeat_byte(0x11);
+
+        assert!(map.insert(&mut storage, key, U256::ZERO)?);
+        assert!(map.contains(&storage, &key)?);
+        assert_eq!(map.get(&storage, &key)?, Some(U256::ZERO));
+        assert_eq!(map.len(&storage)?, 1);
+
+        assert!(map.remove(&mut storage, &key)?);
+        assert!(!map.contains(&storage, &key)?);
+        assert_eq!(map.get(&storage, &key)?, None);
+        assert!(map.is_empty(&storage)?);
+        assert!(!map.remove(&mut storage, &key)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_remove_swaps_last_key_into_place() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+        let mut map = EnumerableMapping::<Address, u64>::new(U256::ZERO);
+        let [a, b, c] = [1u8, 2, 3].map(Address::repeat_byte);
+
+        map.insert(&mut storage, a, 1)?;
+        map.insert(&mut storage, b, 2)?;
+        map.insert(&mut storage, c, 3)?;
+        assert!(map.remove(&mut storage, &a)?);
+
+        let keys = VecHandler::<Address>::new(U256::ZERO);
+        assert_eq!(keys.read(&storage)?, vec![c, b]);
+        assert_eq!(storage.load(map.position_slot(&c))?, U256::ONE);
+        assert_eq!(map.get(&storage, &c)?, Some(3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_remove_rejects_corrupt_position() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+        let mut map = EnumerableMapping::<Address, u64>::new(U256::from(9));
+        let [a, b] = [1u8, 2].map(Address::repeat_byte);
+        map.insert(&mut storage, a, 1)?;
+
+        // a position past the end of the key array
+        storage.store(map.position_slot(&b), U256::from(2))?;
+        assert!(matches!(
+            map.remove(&mut storage, &b),
+            Err(InteropError::InvalidPosition { len: 1, .. })
+        ));
+
+        // a position that doesn't fit in a usize
+        storage.store(map.position_slot(&b), U256::MAX)?;
+        assert!(matches!(
+            map.remove(&mut storage, &b),
+            Err(InteropError::InvalidPosition { position, len: 1 }) if position == U256::MAX
+        ));
+
+        // a position left behind by an emptied key array
+        assert!(map.remove(&mut storage, &a)?);
+        storage.store(map.position_slot(&a), U256::ONE)?;
+        assert!(matches!(
+            map.remove(&mut storage, &a),
+            Err(InteropError::InvalidPosition { len: 0, .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_keys_order_after_insert_and_remove() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+        let mut map = EnumerableMapping::<U256, u64>::new(U256::from(20));
+
+        let keys = |ids: &[u64]| ids.iter().map(|id| U256::from(*id)).collect::<Vec<_>>();
+
+        for id in 1..=5u64 {
+            assert!(map.insert(&mut storage, U256::from(id), id * 10)?);
+        }
+        assert!(!map.insert(&mut storage, U256::from(3), 33)?);
+        assert_eq!(map.keys(&storage)?, keys(&[1, 2, 3, 4, 5]));
+
+        assert!(map.remove(&mut storage, &U256::from(2))?);
+        assert_eq!(map.keys(&storage)?, keys(&[1, 5, 3, 4]));
+        assert!(map.remove(&mut storage, &U256::from(4))?);
+        assert_eq!(map.keys(&storage)?, keys(&[1, 5, 3]));
+
+        map.insert(&mut storage, U256::from(6), 60)?;
+        assert_eq!(map.keys(&storage)?, keys(&[1, 5, 3, 6]));
+        assert_eq!(map.len(&storage)?, 4);
+
+        let values = map
+            .keys(&storage)?
+            .iter()
+            .map(|key| map.get(&storage, key))
+            .collect::<Result<Vec<_>>>()?;
+        assert_eq!(values, [Some(10), Some(50), Some(33), Some(60)]);
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+//! Single-byte storage for fieldless enums.
+
+/// Conversion between a fieldless enum and its one-byte storage discriminant.
+///
+/// Implemented by [`impl_packable_enum!`](crate::impl_packable_enum), which also provides the
+/// `StorableType` and `Packable` impls needed to store the enum like a `uint8`.
+pub trait PackableEnum: Sized {
+    fn to_u8(&self) -> u8;
+    fn from_u8(value: u8) -> Option<Self>;
+}
+
+/// Implements [`PackableEnum`], `StorableType` (`Layout::Bytes(1)`), `Packable` and
+/// `DescribeLayout` for an enum.
+///
+/// Loading an unknown discriminant fails with `InteropError::InvalidEnumDiscriminant`.
+///
+/// ```
+/// use tempo_storage_interop::impl_packable_enum;
+///
+/// enum Status {
+///     Active,
+///     Paused,
+/// }
+///
+/// impl_packable_enum!(Status, { Active = 0, Paused = 1 });
+/// ```
+#[macro_export]
+macro_rules! impl_packable_enum {
+    ($ty:ty, { $($variant:ident = $value:literal),+ $(,)? }) => {
+        impl $crate::PackableEnum for $ty {
+            fn to_u8(&self) -> u8 {
+                match self {
+                    $(Self::$variant => $value,)+
+                }
+            }
+
+            fn from_u8(value: u8) -> Option<Self> {
+                match value {
+                    $($value => Some(Self::$variant),)+
+                    _ => None,
+                }
+            }
+        }
+
+        impl $crate::__private::OnlyPrimitives for $ty {}
+
+        impl $crate::StorableType for $ty {
+            const LAYOUT: $crate::Layout = $crate::Layout::Bytes(1);
+            type Handler = $crate::Slot<Self>;
+
+            fn handle(slot: $crate::__private::U256, ctx: $crate::LayoutCtx) -> Self::Handler {
+                $crate::Slot::new_with_ctx(slot, ctx)
+            }
+        }
+
+        impl $crate::DescribeLayout for $ty {
+            fn storage_layout() -> $crate::StorageLayout {
+                $crate::StorageLayout::inplace::<Self>(concat!("enum ", stringify!($ty)))
+            }
+        }
+
+        impl $crate::Packable for $ty {
+            fn to_word(&self) -> $crate::__private::U256 {
+                $crate::__private::U256::from($crate::PackableEnum::to_u8(self))
+            }
+
+            fn from_word(word: $crate::__private::U256) -> $crate::Result<Self> {
+                let value = word.to_be_bytes::<32>()[31];
+                <Self as $crate::PackableEnum>::from_u8(value)
+                    .ok_or($crate::InteropError::InvalidEnumDiscriminant { value })
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::{Address, U256};
+
+    use crate::{Handler, InteropError, Result, Slot, StorageOps, test_util::MemoryStorage};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Status {
+        Active,
+        Paused,
+    }
+
+    impl_packable_enum!(Status, { Active = 0, Paused = 1 });
+
+    #[test]
+    fn test_enum_round_trip_packed() -> Result<()> {
+        let mut storage = MemoryStorage::new();
+        let mut slot = Slot::<(Status, Address)>::new(U256::ZERO);
+        let value = (Status::Paused, Address::repeat_byte(0x11));
+
+        slot.write(&mut storage, value)?;
+        assert_eq!(storage.load(U256::ZERO)? & U256::from(0xff), U256::from(1));
+        assert_eq!(slot.read(&storage)?, value);
+        Ok(())
+    }
+
+    #[test]
+    fn test_enum_invalid_discriminant() -> Result<()> {
+        let mut storage = MemoryStorage::new();
+        storage.store(U256::ZERO, U256::from(2))?;
+
+        let slot = Slot::<Status>::new(U256::ZERO);
+        assert!(matches!(
+            slot.read(&storage),
+            Err(InteropError::InvalidEnumDiscriminant { value: 2 })
+        ));
+        Ok(())
+    }
+}
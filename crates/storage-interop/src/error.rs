@@ -1,19 +1,97 @@
+use alloy_primitives::{Address, U256};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum InteropError {
-    #[error("packed value spans slot boundary: offset={offset}, bytes={bytes}")]
-    PackedSlotOverflow { offset: usize, bytes: usize },
+    #[error("packed value spans slot boundary: {}offset={offset}, bytes={bytes}", at_slot(.slot))]
+    PackedSlotOverflow {
+        slot: Option<U256>,
+        offset: usize,
+        bytes: usize,
+    },
+    #[error("packed field overlaps a previous field: offset={offset}, bytes={bytes}")]
+    OverlappingPackedField { offset: usize, bytes: usize },
+    #[error("value of {value_bits} bits does not fit in a {field_bytes}-byte field")]
+    ValueTooWide {
+        value_bits: usize,
+        field_bytes: usize,
+    },
+    #[error("index {index} out of bounds for length {len}")]
+    IndexOutOfBounds { index: usize, len: usize },
+    #[error("buffer of length {actual} does not match array length {expected}")]
+    LengthMismatch { expected: usize, actual: usize },
     #[error("invalid boolean value: {0}")]
     InvalidBool(u64),
+    #[error("invalid enum discriminant: {value}")]
+    InvalidEnumDiscriminant { value: u8 },
+    #[error("invalid char code point: {0:#x}")]
+    InvalidChar(u32),
     #[error("invalid signed value encoding")]
     InvalidSignedEncoding,
+    #[error("address word has non-zero upper bytes: {0:#x}")]
+    DirtyAddressWord(U256),
     #[error("invalid utf-8 string data")]
     InvalidUtf8,
+    #[error("stored length {claimed} exceeds the maximum of {max}")]
+    InvalidLength { claimed: U256, max: usize },
+    #[error("stored position {position} is out of range for {len} keys")]
+    InvalidPosition { position: U256, len: usize },
+    #[error("invalid storage dump: {0}")]
+    InvalidStorageDump(String),
     #[error("out of gas")]
     OutOfGas,
+    #[error("log has {count} topics, at most 4 are allowed")]
+    TooManyLogTopics { count: usize },
+    #[error("contract is already initialized or initializing")]
+    InvalidInitialization,
+    #[error("value transfer in static context")]
+    StaticValueTransfer,
+    #[error("contract creation in static context")]
+    StaticCreate,
+    #[error("account destruction in static context")]
+    StaticSelfDestruct,
+    #[error("deleting account {0} requires a journaled self-destruct")]
+    UnsupportedAccountDeletion(Address),
+    #[error("code size {size} exceeds the EIP-170 limit")]
+    CodeTooLarge { size: usize },
+    #[error("code starting with 0xEF is rejected (EIP-3541)")]
+    InvalidInitCode,
+    #[error("no caller set for calls and deployments made by the provider")]
+    MissingCaller,
+    #[error("no precompile registered at {0}")]
+    UnknownPrecompile(Address),
+    #[error("{name} is not supported by spec {spec}")]
+    UnsupportedOpcode { name: &'static str, spec: String },
     #[error("runtime error: {0}")]
     RuntimeError(String),
+    /// Error raised by the EVM internals, kept as the [`source`](std::error::Error::source) so
+    /// callers can downcast it.
+    #[cfg(feature = "revm")]
+    #[error("runtime error: {0}")]
+    EvmInternals(#[from] alloy_evm::EvmInternalsError),
+}
+
+impl InteropError {
+    /// Records `slot` on a packing error that was raised without knowing its slot.
+    pub(crate) fn with_slot(self, slot: U256) -> Self {
+        match self {
+            Self::PackedSlotOverflow {
+                slot: None,
+                offset,
+                bytes,
+            } => Self::PackedSlotOverflow {
+                slot: Some(slot),
+                offset,
+                bytes,
+            },
+            err => err,
+        }
+    }
+}
+
+fn at_slot(slot: &Option<U256>) -> String {
+    slot.map(|slot| format!("slot={slot:#x}, "))
+        .unwrap_or_default()
 }
 
 pub type Result<T> = std::result::Result<T, InteropError>;
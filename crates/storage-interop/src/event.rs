@@ -0,0 +1,129 @@
+//! Solidity-compatible event log encoding.
+
+use alloy_primitives::{B256, Bytes, LogData, U256, keccak256};
+
+use crate::layout::Packable;
+
+#[derive(Debug, Clone)]
+enum DataArg {
+    Word(U256),
+    Dynamic(Vec<u8>),
+}
+
+/// Builds the topics and ABI-encoded data of an event, in declaration order.
+///
+/// Indexed value types are stored as their 32-byte word, while indexed `string`/`bytes`
+/// arguments are replaced by the keccak256 hash of their contents, as Solidity does.
+/// Non-indexed arguments are ABI-encoded into the log data, with dynamic values placed in the
+/// tail and referenced by offset from the head.
+#[derive(Debug, Clone, Default)]
+pub struct EventBuilder {
+    topics: Vec<B256>,
+    data: Vec<DataArg>,
+}
+
+impl EventBuilder {
+    /// Starts a non-anonymous event, using the hash of `signature` as `topic0`.
+    pub fn new(signature: &str) -> Self {
+        Self {
+            topics: vec![keccak256(signature)],
+            data: Vec::new(),
+        }
+    }
+
+    /// Starts an anonymous event, which has no signature topic.
+    pub fn anonymous() -> Self {
+        Self::default()
+    }
+
+    pub fn indexed<T: Packable>(mut self, value: &T) -> Self {
+        self.topics.push(B256::from(value.to_word()));
+        self
+    }
+
+    pub fn indexed_dynamic(mut self, value: impl AsRef<[u8]>) -> Self {
+        self.topics.push(keccak256(value));
+        self
+    }
+
+    pub fn data<T: Packable>(mut self, value: &T) -> Self {
+        self.data.push(DataArg::Word(value.to_word()));
+        self
+    }
+
+    pub fn data_dynamic(mut self, value: impl AsRef<[u8]>) -> Self {
+        self.data.push(DataArg::Dynamic(value.as_ref().to_vec()));
+        self
+    }
+
+    pub fn build(self) -> LogData {
+        LogData::new_unchecked(self.topics, encode_data(&self.data))
+    }
+}
+
+fn encode_data(args: &[DataArg]) -> Bytes {
+    let mut head = Vec::with_capacity(args.len() * 32);
+    let mut tail = Vec::new();
+
+    for arg in args {
+        match arg {
+            DataArg::Word(word) => head.extend_from_slice(&word.to_be_bytes::<32>()),
+            DataArg::Dynamic(value) => {
+                let offset = args.len() * 32 + tail.len();
+                head.extend_from_slice(&U256::from(offset).to_be_bytes::<32>());
+
+                tail.extend_from_slice(&U256::from(value.len()).to_be_bytes::<32>());
+                tail.extend_from_slice(value);
+                tail.resize(tail.len().next_multiple_of(32), 0);
+            }
+        }
+    }
+
+    head.extend_from_slice(&tail);
+    head.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, b256, hex};
+
+    #[test]
+    fn test_indexed_string_topic_is_hashed() {
+        // event Registered(string indexed name, address indexed owner)
+        let owner = Address::repeat_byte(0x01);
+        let log = EventBuilder::new("Registered(string,address)")
+            .indexed_dynamic("hello")
+            .indexed(&owner)
+            .build();
+
+        assert_eq!(log.topics()[0], keccak256("Registered(string,address)"));
+        assert_eq!(
+            log.topics()[1],
+            b256!("1c8aff950685c2ed4bc3174f3472287b56d9517b9c948127319a09a7a36deac8")
+        );
+        assert_eq!(log.topics()[2], owner.into_word());
+        assert!(log.data.is_empty());
+    }
+
+    #[test]
+    fn test_non_indexed_dynamic_data_uses_offsets() {
+        // event Note(uint64 indexed id, string text, uint256 amount)
+        let log = EventBuilder::new("Note(uint64,string,uint256)")
+            .indexed(&7u64)
+            .data_dynamic("hi")
+            .data(&U256::from(5))
+            .build();
+
+        assert_eq!(log.topics()[1], B256::with_last_byte(7));
+        assert_eq!(
+            log.data.as_ref(),
+            hex!(
+                "0000000000000000000000000000000000000000000000000000000000000040"
+                "0000000000000000000000000000000000000000000000000000000000000005"
+                "0000000000000000000000000000000000000000000000000000000000000002"
+                "6869000000000000000000000000000000000000000000000000000000000000"
+            )
+        );
+    }
+}
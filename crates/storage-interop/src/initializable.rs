@@ -0,0 +1,137 @@
+//! Initialization guard mirroring OpenZeppelin's (v5) `Initializable`.
+//!
+//! The state is OZ's `struct InitializableStorage { uint64 _initialized; bool _initializing; }`,
+//! packed into a single slot with `_initialized` in the low 8 bytes and `_initializing` in the
+//! byte right above it, stored at OZ's ERC-7201 namespace slot by default.
+
+use alloy_primitives::{U256, uint};
+
+use crate::{
+    InteropError, Result, event::EventBuilder, layout::Handler, runtime_context::RuntimeContext,
+    runtime_provider::PrecompileStorageProvider, slot::Slot,
+};
+
+#[derive(Debug, Clone)]
+pub struct Initializable {
+    slot: U256,
+}
+
+impl Default for Initializable {
+    fn default() -> Self {
+        Self::new(Self::STORAGE_SLOT)
+    }
+}
+
+impl Initializable {
+    /// `erc7201:openzeppelin.storage.Initializable`, the slot used by OZ v5 upgradeable contracts.
+    pub const STORAGE_SLOT: U256 =
+        uint!(0xf0c57e16840df040f15088dc2f81fe391c3923bec73e23a9662efc9c229c6a00_U256);
+
+    #[inline]
+    pub fn new(slot: U256) -> Self {
+        Self { slot }
+    }
+
+    #[inline]
+    pub const fn slot(&self) -> U256 {
+        self.slot
+    }
+
+    /// Returns the highest version the contract has been initialized to.
+    pub fn initialized_version<P: PrecompileStorageProvider>(
+        &self,
+        ctx: &mut RuntimeContext<'_, P>,
+    ) -> Result<u64> {
+        Ok(self.state(ctx)?.0)
+    }
+
+    pub fn is_initializing<P: PrecompileStorageProvider>(
+        &self,
+        ctx: &mut RuntimeContext<'_, P>,
+    ) -> Result<bool> {
+        Ok(self.state(ctx)?.1)
+    }
+
+    /// Runs `init` as the initializer for `version`, like OZ's `reinitializer(version)`.
+    ///
+    /// Fails with [`InteropError::InvalidInitialization`] if the contract is already
+    /// initializing or has been initialized to `version` or higher. Emits `Initialized(version)`
+    /// once `init` succeeds.
+    pub fn initialize<'a, P, R>(
+        &self,
+        ctx: &mut RuntimeContext<'a, P>,
+        version: u64,
+        init: impl FnOnce(&mut RuntimeContext<'a, P>) -> Result<R>,
+    ) -> Result<R>
+    where
+        P: PrecompileStorageProvider,
+    {
+        let (initialized, initializing) = self.state(ctx)?;
+        if initializing || initialized >= version {
+            return Err(InteropError::InvalidInitialization);
+        }
+
+        self.set_state(ctx, (version, true))?;
+        let result = init(ctx)?;
+        self.set_state(ctx, (version, false))?;
+
+        ctx.emit_event(EventBuilder::new("Initialized(uint64)").data(&version))?;
+        Ok(result)
+    }
+
+    fn state<P: PrecompileStorageProvider>(
+        &self,
+        ctx: &mut RuntimeContext<'_, P>,
+    ) -> Result<(u64, bool)> {
+        Slot::<(u64, bool)>::new(self.slot).read(&ctx.storage_ops())
+    }
+
+    fn set_state<P: PrecompileStorageProvider>(
+        &self,
+        ctx: &mut RuntimeContext<'_, P>,
+        state: (u64, bool),
+    ) -> Result<()> {
+        Slot::<(u64, bool)>::new(self.slot).write(&mut ctx.storage_ops(), state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::{Address, keccak256};
+
+    use super::*;
+    use crate::{MockProvider, storage::StorageOps};
+
+    #[test]
+    fn test_storage_slot_matches_erc7201() {
+        let namespace = U256::from_be_bytes(keccak256("openzeppelin.storage.Initializable").0);
+        let slot = U256::from_be_bytes(keccak256((namespace - U256::ONE).to_be_bytes::<32>()).0)
+            & !U256::from(0xff);
+        assert_eq!(slot, Initializable::STORAGE_SLOT);
+    }
+
+    #[test]
+    fn test_reinitialize_requires_higher_version() -> Result<()> {
+        let mut provider = MockProvider::new();
+        let mut ctx = RuntimeContext::new(&mut provider, Address::repeat_byte(0x01));
+        let guard = Initializable::default();
+
+        let seen = guard.initialize(&mut ctx, 1, |ctx| guard.is_initializing(ctx))?;
+        assert!(seen);
+        assert!(!guard.is_initializing(&mut ctx)?);
+        assert_eq!(
+            ctx.storage_ops().load(Initializable::STORAGE_SLOT)?,
+            U256::from(1)
+        );
+
+        assert!(matches!(
+            guard.initialize(&mut ctx, 1, |_| Ok(())),
+            Err(InteropError::InvalidInitialization)
+        ));
+
+        guard.initialize(&mut ctx, 2, |_| Ok(()))?;
+        assert_eq!(guard.initialized_version(&mut ctx)?, 2);
+        assert_eq!(provider.logs().len(), 2);
+        Ok(())
+    }
+}
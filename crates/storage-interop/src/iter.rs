@@ -0,0 +1,97 @@
+use alloy_primitives::U256;
+use std::marker::PhantomData;
+
+use crate::{
+    Result,
+    layout::{LayoutCtx, Storable},
+    packing::{PackedSlot, calc_element_loc, element_slot_offset},
+    storage::StorageOps,
+};
+
+/// Iterator over consecutive elements of an array-like region, in slot order.
+///
+/// Packed elements sharing a slot are decoded from a single `load` of that slot.
+pub struct ElementIter<'a, T, S> {
+    storage: &'a S,
+    data_start: U256,
+    index: usize,
+    len: usize,
+    cached: Option<(usize, PackedSlot)>,
+    _ty: PhantomData<T>,
+}
+
+impl<'a, T, S> ElementIter<'a, T, S>
+where
+    T: Storable,
+    S: StorageOps,
+{
+    #[inline]
+    pub(crate) fn new(storage: &'a S, data_start: U256, len: usize) -> Self {
+        Self {
+            storage,
+            data_start,
+            index: 0,
+            len,
+            cached: None,
+            _ty: PhantomData,
+        }
+    }
+
+    fn load_packed(&mut self, index: usize) -> Result<T> {
+        let loc = calc_element_loc(index, T::BYTES);
+
+        let slot = self.data_start + U256::from(loc.offset_slots);
+
+        let word = match &self.cached {
+            Some((slot_idx, word)) if *slot_idx == loc.offset_slots => word,
+            _ => {
+                let value = self.storage.load(slot)?;
+                &self.cached.insert((loc.offset_slots, PackedSlot(value))).1
+            }
+        };
+
+        T::load(word, U256::ZERO, LayoutCtx::packed(loc.offset_bytes))
+            .map_err(|err| err.with_slot(slot))
+    }
+}
+
+impl<T, S> Iterator for ElementIter<'_, T, S>
+where
+    T: Storable,
+    S: StorageOps,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.len {
+            return None;
+        }
+
+        let index = self.index;
+        self.index += 1;
+
+        let result = if T::BYTES <= 16 {
+            self.load_packed(index)
+        } else {
+            let slot = self.data_start + element_slot_offset(index, T::SLOTS);
+            T::load(self.storage, slot, LayoutCtx::FULL)
+        };
+
+        if result.is_err() {
+            self.index = self.len;
+        }
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.len - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<T, S> ExactSizeIterator for ElementIter<'_, T, S>
+where
+    T: Storable,
+    S: StorageOps,
+{
+}
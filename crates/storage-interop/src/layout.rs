@@ -33,14 +33,7 @@ impl Layout {
     pub const fn bytes(&self) -> usize {
         match self {
             Self::Bytes(n) => *n,
-            Self::Slots(n) => {
-                let (mut i, mut result) = (0, 0);
-                while i < *n {
-                    result += 32;
-                    i += 1;
-                }
-                result
-            }
+            Self::Slots(n) => *n * 32,
         }
     }
 }
@@ -83,6 +76,16 @@ pub trait Handler<T: Storable> {
     fn read<S: StorageOps>(&self, storage: &S) -> Result<T>;
     fn write<S: StorageOps>(&mut self, storage: &mut S, value: T) -> Result<()>;
     fn delete<S: StorageOps>(&mut self, storage: &mut S) -> Result<()>;
+
+    /// Reads the value, applies `f` and writes the result back.
+    fn modify<S, F>(&mut self, storage: &mut S, f: F) -> Result<()>
+    where
+        S: StorageOps,
+        F: FnOnce(T) -> T,
+    {
+        let value = self.read(storage)?;
+        self.write(storage, f(value))
+    }
 }
 
 pub trait Storable: StorableType + Sized {
@@ -106,6 +109,11 @@ pub trait Storable: StorableType + Sized {
             }
         }
     }
+
+    /// Returns every slot backing a value stored at `slot`, including dynamic data regions.
+    fn raw_slots<S: StorageOps>(_storage: &S, slot: U256) -> Result<Vec<U256>> {
+        Ok((0..Self::SLOTS).map(|i| slot + U256::from(i)).collect())
+    }
 }
 
 pub trait Packable: sealed::OnlyPrimitives + StorableType {
@@ -113,6 +121,15 @@ pub trait Packable: sealed::OnlyPrimitives + StorableType {
     fn from_word(word: U256) -> Result<Self>
     where
         Self: Sized;
+
+    /// Strict variant of [`Packable::from_word`] that rejects words with bits set outside of
+    /// the type's encoding. Defaults to `from_word` for types whose decoding is already strict.
+    fn from_word_checked(word: U256) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        Self::from_word(word)
+    }
 }
 
 impl<T: Packable> Storable for T {
@@ -136,6 +153,14 @@ impl<T: Packable> Storable for T {
         match ctx.packed_offset() {
             None => storage.store(slot, self.to_word()),
             Some(offset) => {
+                // checked at offset 0 so only the value's width is asserted; an offset that
+                // overflows the slot is reported as an error below
+                debug_assert!(
+                    packing::insert_packed_value_checked(U256::ZERO, self, 0, Self::BYTES).is_ok(),
+                    "packed value does not fit in {} bytes",
+                    Self::BYTES
+                );
+
                 let current = storage.load(slot)?;
                 let updated = packing::insert_packed_value(current, self, offset, Self::BYTES)?;
                 storage.store(slot, updated)
@@ -143,3 +168,59 @@ impl<T: Packable> Storable for T {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Slot, packing::calc_field_locs, test_util::MemoryStorage};
+    use alloy_primitives::Address;
+
+    #[test]
+    fn test_modify_packed_field() -> Result<()> {
+        // struct { address owner; uint32 counter; uint64 limit; }
+        let base = U256::from(6);
+        let locs = calc_field_locs([Address::LAYOUT, u32::LAYOUT, u64::LAYOUT]);
+        let owner = Address::repeat_byte(0x33);
+
+        let mut storage = MemoryStorage::new();
+        Slot::<(Address, u32, u64)>::new(base).write(&mut storage, (owner, 41, u64::MAX))?;
+
+        let mut counter = Slot::<u32>::new_at_loc(base, locs[1])?;
+        counter.modify(&mut storage, |n| n + 1)?;
+
+        assert_eq!(
+            Slot::<(Address, u32, u64)>::new(base).read(&storage)?,
+            (owner, 42, u64::MAX)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_packed_store_of_full_width_values() -> Result<()> {
+        // extreme values of every width must pass the debug width assertion
+        let mut storage = MemoryStorage::new();
+        Slot::<i8>::new_with_ctx(U256::ONE, LayoutCtx::packed(31)).write(&mut storage, i8::MIN)?;
+        Slot::<u16>::new_with_ctx(U256::ONE, LayoutCtx::packed(29))
+            .write(&mut storage, u16::MAX)?;
+        Slot::<i64>::new_with_ctx(U256::ONE, LayoutCtx::packed(21)).write(&mut storage, -1)?;
+        Slot::<Address>::new_with_ctx(U256::ONE, LayoutCtx::packed(1))
+            .write(&mut storage, Address::repeat_byte(0xff))?;
+
+        assert_eq!(
+            Slot::<i8>::new_with_ctx(U256::ONE, LayoutCtx::packed(31)).read(&storage)?,
+            i8::MIN
+        );
+        assert_eq!(
+            Slot::<i64>::new_with_ctx(U256::ONE, LayoutCtx::packed(21)).read(&storage)?,
+            -1
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_slots_bytes_in_const_context() {
+        const BYTES: usize = Layout::Slots(1000).bytes();
+        assert_eq!(BYTES, 32000);
+        assert_eq!(<[U256; 100_000]>::LAYOUT.bytes(), 3_200_000);
+    }
+}
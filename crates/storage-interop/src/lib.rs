@@ -1,31 +1,124 @@
 //! Storage interoperability primitives for Rust and Solidity contracts.
+//!
+//! A contract's storage layout can be declared as a struct of handlers, each constructed at
+//! the slot solc assigns to the matching state variable:
+//!
+//! ```
+//! use alloy_primitives::{Address, U256};
+//! use tempo_storage_interop::{ArrayHandler, BytesLikeHandler, Mapping, Slot, VecHandler};
+//!
+//! // contract Token {
+//! //     address owner;                        // slot 0
+//! //     mapping(address => uint256) balances; // slot 1
+//! //     address[] holders;                    // slot 2
+//! //     string name;                          // slot 3
+//! //     uint64[4] limits;                     // slot 4
+//! // }
+//! struct Token {
+//!     owner: Slot<Address>,
+//!     balances: Mapping<Address, U256>,
+//!     holders: VecHandler<Address>,
+//!     name: BytesLikeHandler<String>,
+//!     limits: ArrayHandler<u64, 4>,
+//! }
+//!
+//! impl Default for Token {
+//!     fn default() -> Self {
+//!         Self {
+//!             owner: Slot::default(),
+//!             balances: Mapping::at_slot(U256::from(1)),
+//!             holders: VecHandler::at_slot(U256::from(2)),
+//!             name: BytesLikeHandler::at_slot(U256::from(3)),
+//!             limits: ArrayHandler::at_slot(U256::from(4)),
+//!         }
+//!     }
+//! }
+//! ```
 
+mod describe;
+mod dump;
 mod error;
+mod event;
 mod layout;
 mod packing;
 mod slot;
 mod storage;
+mod structs;
 mod types;
 mod array;
+mod bitvec;
+mod boxed;
 mod bytes_like;
+mod enumerable;
+mod enums;
+mod initializable;
+mod iter;
 mod mapping;
+mod ordered;
+mod overlay;
+#[cfg(any(test, feature = "test-utils"))]
+mod memory;
+#[cfg(any(test, feature = "test-utils"))]
+mod metered;
 mod vec;
 mod runtime;
+mod trace;
+mod transparent;
+mod tuple;
+#[cfg(test)]
+mod test_util;
 
+pub use describe::{DescribeLayout, Encoding, FieldDescription, StorageLayout};
+pub use dump::DumpStorage;
 pub use error::{InteropError, Result};
+pub use event::EventBuilder;
 pub use layout::{Handler, Layout, LayoutCtx, Packable, Storable, StorableType};
 pub use packing::{
-    FieldLocation, PackedSlot, calc_element_loc, calc_element_offset, calc_element_slot,
-    calc_packed_slot_count, create_element_mask, extract_packed_value, insert_packed_value,
-    zero_packed_value,
+    FieldLocation, MAX_DYNAMIC_LENGTH, PackedSlot, PackedSlotBuilder, calc_element_loc,
+    calc_element_offset, calc_element_slot, calc_packed_slot_count, create_element_mask,
+    data_region_slot, data_region_slot_with, decode_length, decode_length_with_max,
+    elements_per_slot, extract_packed_value, fixed_array_element_slot, insert_packed_value,
+    insert_packed_value_checked, layout_of, zero_packed_value,
 };
-pub use slot::Slot;
-pub use storage::{StorageKey, StorageOps};
+pub use slot::{Slot, array_element};
+pub use trace::{StorageAccess, TracingStorage};
+pub use storage::{Keccak, Keccak256, StorageKey, StorageOps};
+pub use structs::{FieldInfo, StructLayout};
 pub use types::*;
 pub use array::ArrayHandler;
-pub use bytes_like::BytesLikeHandler;
-pub use mapping::Mapping;
-pub use vec::VecHandler;
-pub use runtime::{PrecompileStorageProvider, RuntimeContext, RuntimeStorageOps, StorageMode};
+pub use bitvec::{BitVec, BitVecHandler};
+pub use boxed::Boxed;
+pub use bytes_like::{ByteChunks, BytesLikeHandler};
+pub use enumerable::EnumerableMapping;
+pub use enums::PackableEnum;
+pub use initializable::Initializable;
+pub use iter::ElementIter;
+pub use mapping::{Mapping, MappingEntry};
+pub use ordered::OrderedMapHandler;
+pub use overlay::OverlayStorage;
+#[cfg(any(test, feature = "test-utils"))]
+pub use memory::MemoryStorage;
+#[cfg(any(test, feature = "test-utils"))]
+pub use metered::{GasComparison, GasGolden, MeteredStorage, assert_gas};
+pub use vec::{VecHandler, dynamic_array_element_slot};
+pub use runtime::{
+    CallOutcome, DualStorageOps, MAX_LOG_TOPICS, PrecompileFn, PrecompileRegistry,
+    PrecompileStorageProvider, RuntimeContext, RuntimeStorageOps, StorageBatch, StorageMode,
+    StorageView,
+};
+#[cfg(any(test, feature = "test-utils"))]
+pub use runtime::{MockAccount, MockProvider};
 #[cfg(feature = "revm")]
 pub use runtime::RevmStorageProvider;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::{
+        describe::describe_fields,
+        packing::{calc_field_locs, calc_struct_slots},
+        structs::field_infos,
+        tuple::{FieldReader, FieldWriter},
+        types::sealed::OnlyPrimitives,
+    };
+    pub use alloy_primitives::U256;
+}
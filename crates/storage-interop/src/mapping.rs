@@ -2,8 +2,9 @@ use alloy_primitives::U256;
 use std::marker::PhantomData;
 
 use crate::{
-    layout::{Layout, LayoutCtx, StorableType},
-    storage::StorageKey,
+    Result,
+    layout::{Handler, Layout, LayoutCtx, Storable, StorableType},
+    storage::{StorageKey, StorageOps},
 };
 
 #[derive(Debug, Clone)]
@@ -21,6 +22,12 @@ impl<K, V> Mapping<K, V> {
         }
     }
 
+    /// Equivalent to [`Mapping::new`].
+    #[inline]
+    pub fn at_slot(slot: U256) -> Self {
+        Self::new(slot)
+    }
+
     #[inline]
     pub const fn slot(&self) -> U256 {
         self.base_slot
@@ -34,6 +41,50 @@ impl<K, V> Mapping<K, V> {
         V::handle(key.mapping_slot(self.base_slot), LayoutCtx::FULL)
     }
 
+    /// Resolves the slot of `key` once, for repeated reads and writes of the same entry.
+    pub fn entry(&self, key: K) -> MappingEntry<V>
+    where
+        K: StorageKey,
+    {
+        MappingEntry {
+            slot: key.mapping_slot(self.base_slot),
+            _ty: PhantomData,
+        }
+    }
+
+    /// Deletes the value stored under `key`, including the data region of dynamic values.
+    pub fn delete<S: StorageOps>(&mut self, storage: &mut S, key: K) -> Result<()>
+    where
+        K: StorageKey,
+        V: Storable,
+    {
+        V::delete(storage, key.mapping_slot(self.base_slot), LayoutCtx::FULL)
+    }
+
+    /// Resolves the base slot of the value stored under each key, in the order given.
+    ///
+    /// Only the per-key slots are returned: for multi-slot values the remaining slots follow
+    /// consecutively, and for dynamic values (`Vec`, `Bytes`, `String`) the data region
+    /// depends on the stored length and must be resolved separately.
+    pub fn slots_for_keys(&self, keys: &[K]) -> Vec<U256>
+    where
+        K: StorageKey,
+    {
+        keys.iter()
+            .map(|key| key.mapping_slot(self.base_slot))
+            .collect()
+    }
+
+    /// Returns the raw slot of the field `field_offset_slots` slots into the struct stored under
+    /// `key`.
+    #[inline]
+    pub fn field_slot(&self, key: K, field_offset_slots: usize) -> U256
+    where
+        K: StorageKey,
+    {
+        key.mapping_slot(self.base_slot) + U256::from(field_offset_slots)
+    }
+
     #[inline]
     pub fn at_offset(struct_base_slot: U256, field_offset_slots: usize, key: K) -> V::Handler
     where
@@ -45,6 +96,43 @@ impl<K, V> Mapping<K, V> {
     }
 }
 
+/// Value slot of a single mapping key, see [`Mapping::entry`].
+#[derive(Debug, Clone, Copy)]
+pub struct MappingEntry<V> {
+    slot: U256,
+    _ty: PhantomData<V>,
+}
+
+impl<V> MappingEntry<V> {
+    #[inline]
+    pub const fn slot(&self) -> U256 {
+        self.slot
+    }
+
+    /// Returns the value's own handler, e.g. to index into a nested mapping or array.
+    #[inline]
+    pub fn handler(&self) -> V::Handler
+    where
+        V: StorableType,
+    {
+        V::handle(self.slot, LayoutCtx::FULL)
+    }
+}
+
+impl<V: Storable> Handler<V> for MappingEntry<V> {
+    fn read<S: StorageOps>(&self, storage: &S) -> Result<V> {
+        V::load(storage, self.slot, LayoutCtx::FULL)
+    }
+
+    fn write<S: StorageOps>(&mut self, storage: &mut S, value: V) -> Result<()> {
+        value.store(storage, self.slot, LayoutCtx::FULL)
+    }
+
+    fn delete<S: StorageOps>(&mut self, storage: &mut S) -> Result<()> {
+        V::delete(storage, self.slot, LayoutCtx::FULL)
+    }
+}
+
 impl<K, V> Default for Mapping<K, V> {
     fn default() -> Self {
         Self::new(U256::ZERO)
@@ -55,7 +143,122 @@ impl<K, V> StorableType for Mapping<K, V> {
     const LAYOUT: Layout = Layout::Slots(1);
     type Handler = Self;
 
-    fn handle(slot: U256, _ctx: LayoutCtx) -> Self::Handler {
+    fn handle(slot: U256, ctx: LayoutCtx) -> Self::Handler {
+        debug_assert_eq!(ctx, LayoutCtx::FULL, "Mappings cannot be packed");
         Self::new(slot)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Handler, test_util::MemoryStorage};
+    use alloy_primitives::{Address, keccak256};
+
+    #[test]
+    fn test_slots_for_keys() {
+        let mapping = Mapping::<Address, U256>::new(U256::from(4));
+        let keys = [1u8, 2, 3].map(Address::repeat_byte);
+
+        let expected: Vec<U256> = keys
+            .iter()
+            .map(|key| {
+                let mut preimage = [0u8; 64];
+                preimage[12..32].copy_from_slice(key.as_slice());
+                preimage[63] = 4;
+                U256::from_be_bytes(keccak256(preimage).0)
+            })
+            .collect();
+
+        assert_eq!(mapping.slots_for_keys(&keys), expected);
+    }
+
+    #[test]
+    fn test_delete_clears_dynamic_value() -> Result<()> {
+        let mut storage = MemoryStorage::new();
+        let mut names = Mapping::<Address, String>::new(U256::from(2));
+        let (alice, bob) = (Address::repeat_byte(0xa1), Address::repeat_byte(0xb0));
+        names.at(alice).write(&mut storage, "a".repeat(100))?;
+        names.at(bob).write(&mut storage, "bob".into())?;
+
+        let slots = String::raw_slots(&storage, alice.mapping_slot(names.slot()))?;
+        assert_eq!(slots.len(), 5);
+
+        names.delete(&mut storage, alice)?;
+        for slot in slots {
+            assert_eq!(storage.load(slot)?, U256::ZERO);
+        }
+        assert_eq!(names.at(alice).read(&storage)?, "");
+        assert_eq!(names.at(bob).read(&storage)?, "bob");
+        Ok(())
+    }
+
+    #[test]
+    fn test_entry_caches_key_slot() -> Result<()> {
+        let mut storage = MemoryStorage::new();
+        let balances = Mapping::<Address, U256>::new(U256::from(5));
+        let holder = Address::repeat_byte(0x12);
+
+        let mut entry = balances.entry(holder);
+        assert_eq!(entry.slot(), holder.mapping_slot(balances.slot()));
+
+        for _ in 0..3 {
+            entry.modify(&mut storage, |balance| balance + U256::from(10))?;
+        }
+        assert_eq!(balances.at(holder).read(&storage)?, U256::from(30));
+
+        entry.delete(&mut storage)?;
+        assert_eq!(entry.read(&storage)?, U256::ZERO);
+        Ok(())
+    }
+
+    #[test]
+    fn test_push_to_vec_value() -> Result<()> {
+        let mut storage = MemoryStorage::new();
+        let history = Mapping::<Address, Vec<u64>>::new(U256::from(3));
+        let (alice, bob) = (Address::repeat_byte(0xa1), Address::repeat_byte(0xb0));
+        for value in 1..=5 {
+            history.at(alice).push(&mut storage, value)?;
+        }
+        history.at(bob).push(&mut storage, 9)?;
+
+        // solc layout of `mapping(address => uint64[])` at slot 3: the length lives at
+        // keccak256(key . 3) and the packed elements start at keccak256(length slot)
+        let mut preimage = [0u8; 64];
+        preimage[12..32].copy_from_slice(alice.as_slice());
+        preimage[63] = 3;
+        let len_slot = U256::from_be_bytes(keccak256(preimage).0);
+        let data_slot = U256::from_be_bytes(keccak256(len_slot.to_be_bytes::<32>()).0);
+
+        assert_eq!(history.at(alice).len_slot(), len_slot);
+        assert_eq!(storage.load(len_slot)?, U256::from(5));
+        let first = (U256::from(4) << 192) | (U256::from(3) << 128) | (U256::from(2) << 64);
+        assert_eq!(storage.load(data_slot)?, first | U256::ONE);
+        assert_eq!(storage.load(data_slot + U256::ONE)?, U256::from(5));
+
+        assert_eq!(history.at(alice).read(&storage)?, vec![1, 2, 3, 4, 5]);
+        assert_eq!(history.at(bob).read(&storage)?, vec![9]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_field_slot_of_struct_value() -> Result<()> {
+        // struct Position { uint256 size; address owner; uint256 margin; }
+        // mapping(address => Position) at slot 6
+        let mut storage = MemoryStorage::new();
+        let positions = Mapping::<Address, (U256, Address, U256)>::new(U256::from(6));
+        let trader = Address::repeat_byte(0x77);
+        positions
+            .at(trader)
+            .write(&mut storage, (U256::from(10), trader, U256::from(250)))?;
+
+        let mut preimage = [0u8; 64];
+        preimage[12..32].copy_from_slice(trader.as_slice());
+        preimage[63] = 6;
+        let margin_slot = U256::from_be_bytes(keccak256(preimage).0) + U256::from(2);
+
+        assert_eq!(positions.field_slot(trader, 2), margin_slot);
+        assert_eq!(storage.load(margin_slot)?, U256::from(250));
+        Ok(())
+    }
+}
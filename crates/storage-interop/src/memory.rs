@@ -0,0 +1,119 @@
+//! In-memory [`StorageOps`] backends for tests and tooling.
+//!
+//! Missing slots read as zero, and storing zero removes the slot, so two backends holding the
+//! same logical state always compare equal.
+
+use alloy_primitives::U256;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{Result, storage::StorageOps};
+
+impl StorageOps for HashMap<U256, U256> {
+    fn load(&self, slot: U256) -> Result<U256> {
+        Ok(self.get(&slot).copied().unwrap_or_default())
+    }
+
+    fn store(&mut self, slot: U256, value: U256) -> Result<()> {
+        if value.is_zero() {
+            self.remove(&slot);
+        } else {
+            self.insert(slot, value);
+        }
+        Ok(())
+    }
+}
+
+impl StorageOps for BTreeMap<U256, U256> {
+    fn load(&self, slot: U256) -> Result<U256> {
+        Ok(self.get(&slot).copied().unwrap_or_default())
+    }
+
+    fn store(&mut self, slot: U256, value: U256) -> Result<()> {
+        if value.is_zero() {
+            self.remove(&slot);
+        } else {
+            self.insert(slot, value);
+        }
+        Ok(())
+    }
+}
+
+/// Slot-ordered in-memory storage, convenient for snapshot testing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryStorage {
+    slots: BTreeMap<U256, U256>,
+}
+
+impl MemoryStorage {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns all non-zero `(slot, value)` pairs sorted by slot.
+    pub fn dump_nonzero(&self) -> Vec<(U256, U256)> {
+        self.slots
+            .iter()
+            .filter(|(_, value)| !value.is_zero())
+            .map(|(slot, value)| (*slot, *value))
+            .collect()
+    }
+}
+
+impl FromIterator<(U256, U256)> for MemoryStorage {
+    fn from_iter<I: IntoIterator<Item = (U256, U256)>>(iter: I) -> Self {
+        let mut storage = Self::new();
+        for (slot, value) in iter {
+            // infallible for in-memory maps
+            let _ = storage.slots.store(slot, value);
+        }
+        storage
+    }
+}
+
+impl StorageOps for MemoryStorage {
+    #[inline]
+    fn load(&self, slot: U256) -> Result<U256> {
+        self.slots.load(slot)
+    }
+
+    #[inline]
+    fn store(&mut self, slot: U256, value: U256) -> Result<()> {
+        self.slots.store(slot, value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_slots_read_zero() -> Result<()> {
+        let map = HashMap::<U256, U256>::new();
+        assert_eq!(map.load(U256::from(42))?, U256::ZERO);
+
+        let storage = MemoryStorage::new();
+        assert_eq!(storage.load(U256::MAX)?, U256::ZERO);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dump_nonzero_is_sorted() -> Result<()> {
+        let mut storage = MemoryStorage::from_iter([
+            (U256::from(9), U256::from(3)),
+            (U256::from(1), U256::from(1)),
+            (U256::from(4), U256::ZERO),
+        ]);
+        storage.store(U256::from(5), U256::from(2))?;
+        storage.store(U256::from(9), U256::ZERO)?;
+
+        assert_eq!(
+            storage.dump_nonzero(),
+            vec![
+                (U256::from(1), U256::from(1)),
+                (U256::from(5), U256::from(2)),
+            ]
+        );
+        Ok(())
+    }
+}
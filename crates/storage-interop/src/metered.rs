@@ -0,0 +1,253 @@
+//! Gas-metered storage for catching access-pattern regressions in tests.
+//!
+//! [`MeteredStorage`] prices every access with the post-Berlin SLOAD/SSTORE schedule
+//! (EIP-2929 warm/cold surcharges on top of EIP-2200 net metering), and [`assert_gas`] compares
+//! the cost of an operation against a recorded [`GasGolden`] entry. Refunds are not modelled.
+
+use alloy_primitives::U256;
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+};
+
+use crate::{Result, memory::MemoryStorage, storage::StorageOps};
+
+const COLD_SLOAD_COST: u64 = 2100;
+const WARM_SLOAD_COST: u64 = 100;
+const SSTORE_SET_COST: u64 = 20000;
+const SSTORE_RESET_COST: u64 = 2900;
+
+/// In-memory storage that accumulates the gas an EVM would charge for each access.
+#[derive(Debug, Default)]
+pub struct MeteredStorage {
+    inner: MemoryStorage,
+    /// Warm slots mapped to their value at the start of the transaction.
+    accessed: RefCell<BTreeMap<U256, U256>>,
+    gas_used: Cell<u64>,
+}
+
+impl MeteredStorage {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn gas_used(&self) -> u64 {
+        self.gas_used.get()
+    }
+
+    /// Starts a new transaction: every slot becomes cold again and the gas counter is zeroed.
+    pub fn begin_tx(&mut self) {
+        self.accessed.get_mut().clear();
+        self.gas_used.set(0);
+    }
+
+    /// Runs `f` in a fresh transaction and returns its result with the gas it consumed.
+    pub fn measure<R>(&mut self, f: impl FnOnce(&mut Self) -> Result<R>) -> Result<(R, u64)> {
+        self.begin_tx();
+        let result = f(self)?;
+        Ok((result, self.gas_used()))
+    }
+
+    #[inline]
+    fn charge(&self, gas: u64) {
+        self.gas_used.set(self.gas_used.get() + gas);
+    }
+
+    /// Marks `slot` as warm, returning its original value and whether it was cold.
+    fn access(&self, slot: U256, current: U256) -> (U256, bool) {
+        let mut accessed = self.accessed.borrow_mut();
+        match accessed.get(&slot) {
+            Some(original) => (*original, false),
+            None => {
+                accessed.insert(slot, current);
+                (current, true)
+            }
+        }
+    }
+}
+
+impl StorageOps for MeteredStorage {
+    fn load(&self, slot: U256) -> Result<U256> {
+        let value = self.inner.load(slot)?;
+        let (_, is_cold) = self.access(slot, value);
+        self.charge(if is_cold {
+            COLD_SLOAD_COST
+        } else {
+            WARM_SLOAD_COST
+        });
+        Ok(value)
+    }
+
+    fn store(&mut self, slot: U256, value: U256) -> Result<()> {
+        let current = self.inner.load(slot)?;
+        let (original, is_cold) = self.access(slot, current);
+
+        let mut gas = if current == value || original != current {
+            WARM_SLOAD_COST
+        } else if original.is_zero() {
+            SSTORE_SET_COST
+        } else {
+            SSTORE_RESET_COST
+        };
+        if is_cold {
+            gas += COLD_SLOAD_COST;
+        }
+        self.charge(gas);
+
+        self.inner.store(slot, value)
+    }
+}
+
+/// Gas used by two alternative implementations of the same operation, each measured against
+/// a fresh [`MeteredStorage`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasComparison {
+    pub baseline: u64,
+    pub candidate: u64,
+}
+
+impl GasComparison {
+    pub fn run<A, B>(
+        baseline: impl FnOnce(&mut MeteredStorage) -> Result<A>,
+        candidate: impl FnOnce(&mut MeteredStorage) -> Result<B>,
+    ) -> Result<Self> {
+        let (_, baseline) = MeteredStorage::new().measure(baseline)?;
+        let (_, candidate) = MeteredStorage::new().measure(candidate)?;
+        Ok(Self {
+            baseline,
+            candidate,
+        })
+    }
+
+    /// Candidate gas minus baseline gas; negative when the candidate is cheaper.
+    #[inline]
+    pub fn delta(&self) -> i128 {
+        i128::from(self.candidate) - i128::from(self.baseline)
+    }
+}
+
+/// A recorded gas cost for a named operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasGolden {
+    pub name: &'static str,
+    pub expected: u64,
+    /// Maximum allowed absolute drift from `expected`.
+    pub tolerance: u64,
+}
+
+impl GasGolden {
+    #[inline]
+    pub const fn exact(name: &'static str, expected: u64) -> Self {
+        Self {
+            name,
+            expected,
+            tolerance: 0,
+        }
+    }
+}
+
+/// Measures `f` in a fresh transaction and panics if its gas drifts beyond the golden tolerance.
+#[track_caller]
+pub fn assert_gas<R>(
+    storage: &mut MeteredStorage,
+    golden: GasGolden,
+    f: impl FnOnce(&mut MeteredStorage) -> Result<R>,
+) -> R {
+    let (result, gas_used) = storage
+        .measure(f)
+        .unwrap_or_else(|err| panic!("{}: operation failed: {err}", golden.name));
+
+    assert!(
+        gas_used.abs_diff(golden.expected) <= golden.tolerance,
+        "{}: used {gas_used} gas, golden value is {} (tolerance {})",
+        golden.name,
+        golden.expected,
+        golden.tolerance,
+    );
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Handler, Slot, VecHandler};
+    use alloy_primitives::Address;
+
+    const GOLDEN: &[GasGolden] = &[
+        // cold len + cold data slot loads, then two zero -> non-zero stores
+        GasGolden::exact("vec_push_first", 44_200),
+        // same accesses, but both stores now update non-zero slots
+        GasGolden::exact("vec_push_second", 10_000),
+        // three fields sharing one slot must cost a single cold SLOAD
+        GasGolden::exact("packed_tuple_read", 2_100),
+        // cold len load, then the now warm len store and one cold element store
+        GasGolden::exact("vec_overwrite_same_length", 7_200),
+        // as above with a changed len, plus three cold stores zeroing the stale tail
+        GasGolden::exact("vec_shrink_4_to_1", 25_000),
+    ];
+
+    fn golden(name: &str) -> GasGolden {
+        *GOLDEN
+            .iter()
+            .find(|g| g.name == name)
+            .expect("missing golden entry")
+    }
+
+    #[test]
+    fn test_vec_push_gas() {
+        let mut storage = MeteredStorage::new();
+        let mut vec = VecHandler::<u64>::new(U256::ZERO);
+
+        assert_gas(&mut storage, golden("vec_push_first"), |s| vec.push(s, 1));
+        assert_gas(&mut storage, golden("vec_push_second"), |s| vec.push(s, 2));
+        assert_eq!(vec.read(&storage).unwrap(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_packed_tuple_read_gas() {
+        let mut storage = MeteredStorage::new();
+        let mut slot = Slot::<(u8, Address, u64)>::new(U256::from(3));
+        let value = (7u8, Address::repeat_byte(0xaa), 42u64);
+        slot.write(&mut storage, value).unwrap();
+
+        let read = assert_gas(&mut storage, golden("packed_tuple_read"), |s| slot.read(s));
+        assert_eq!(read, value);
+    }
+
+    #[test]
+    fn test_vec_shrink_gas() {
+        let mut storage = MeteredStorage::new();
+        let mut vec = VecHandler::<U256>::new(U256::ZERO);
+        let values = |range: std::ops::RangeInclusive<u64>| range.map(U256::from).collect();
+
+        vec.write(&mut storage, values(1..=1)).unwrap();
+        assert_gas(&mut storage, golden("vec_overwrite_same_length"), |s| {
+            vec.write(s, values(5..=5))
+        });
+
+        vec.write(&mut storage, values(1..=4)).unwrap();
+        assert_gas(&mut storage, golden("vec_shrink_4_to_1"), |s| {
+            vec.write(s, values(5..=5))
+        });
+        assert_eq!(storage.inner.dump_nonzero().len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "golden value is 2000")]
+    fn test_assert_gas_drift_fails() {
+        let mut storage = MeteredStorage::new();
+        let slot = Slot::<U256>::new(U256::ZERO);
+
+        assert_gas(
+            &mut storage,
+            GasGolden {
+                name: "sload",
+                expected: 2000,
+                tolerance: 50,
+            },
+            |s| slot.read(s),
+        );
+    }
+}
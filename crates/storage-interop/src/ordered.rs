@@ -0,0 +1,258 @@
+//! Dense, key-ordered maps for Rust-side state snapshots.
+//!
+//! This layout has no Solidity equivalent. The entry count lives at `base_slot` and the entries
+//! follow at `keccak256(base_slot)` as `(key, value)` tuples sorted by key, i.e. exactly like a
+//! `Vec<(K, V)>` kept in key order. A `BTreeMap<U256, U256>` therefore takes one length slot
+//! plus two slots per entry. Unlike [`Mapping`](crate::Mapping), the entries are contiguous and
+//! can be enumerated, at the cost of shifting later entries on `insert` and `remove`.
+
+use alloy_primitives::U256;
+use std::{cmp::Ordering, collections::BTreeMap, marker::PhantomData};
+
+use crate::{
+    Result,
+    iter::ElementIter,
+    layout::{Handler, Layout, LayoutCtx, Storable, StorableType},
+    slot::Slot,
+    storage::StorageOps,
+    vec::VecHandler,
+};
+
+impl<K, V> StorableType for BTreeMap<K, V>
+where
+    K: Storable + Ord,
+    V: Storable,
+{
+    const LAYOUT: Layout = Layout::Slots(1);
+    const IS_DYNAMIC: bool = true;
+    type Handler = OrderedMapHandler<K, V>;
+
+    fn handle(slot: U256, ctx: LayoutCtx) -> Self::Handler {
+        debug_assert_eq!(ctx, LayoutCtx::FULL, "Ordered maps cannot be packed");
+        OrderedMapHandler::new(slot)
+    }
+}
+
+impl<K, V> Storable for BTreeMap<K, V>
+where
+    K: Storable + Ord + Clone,
+    V: Storable + Clone,
+{
+    fn load<S: StorageOps>(storage: &S, slot: U256, ctx: LayoutCtx) -> Result<Self> {
+        Ok(Vec::<(K, V)>::load(storage, slot, ctx)?
+            .into_iter()
+            .collect())
+    }
+
+    fn store<S: StorageOps>(&self, storage: &mut S, slot: U256, ctx: LayoutCtx) -> Result<()> {
+        let entries: Vec<(K, V)> = self
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        entries.store(storage, slot, ctx)
+    }
+
+    fn delete<S: StorageOps>(storage: &mut S, slot: U256, ctx: LayoutCtx) -> Result<()> {
+        Vec::<(K, V)>::delete(storage, slot, ctx)
+    }
+
+    fn raw_slots<S: StorageOps>(storage: &S, slot: U256) -> Result<Vec<U256>> {
+        Vec::<(K, V)>::raw_slots(storage, slot)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OrderedMapHandler<K, V> {
+    base_slot: U256,
+    _phantom: PhantomData<(K, V)>,
+}
+
+impl<K, V> Default for OrderedMapHandler<K, V>
+where
+    K: Storable + Ord,
+    V: Storable,
+{
+    fn default() -> Self {
+        Self::new(U256::ZERO)
+    }
+}
+
+impl<K, V> OrderedMapHandler<K, V>
+where
+    K: Storable + Ord,
+    V: Storable,
+{
+    #[inline]
+    pub fn new(base_slot: U256) -> Self {
+        Self {
+            base_slot,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Equivalent to [`OrderedMapHandler::new`].
+    #[inline]
+    pub fn at_slot(slot: U256) -> Self {
+        Self::new(slot)
+    }
+
+    #[inline]
+    pub const fn slot(&self) -> U256 {
+        self.base_slot
+    }
+
+    #[inline]
+    fn entries(&self) -> VecHandler<(K, V)> {
+        VecHandler::new(self.base_slot)
+    }
+
+    #[inline]
+    pub fn len<S: StorageOps>(&self, storage: &S) -> Result<usize> {
+        self.entries().len(storage)
+    }
+
+    #[inline]
+    pub fn is_empty<S: StorageOps>(&self, storage: &S) -> Result<bool> {
+        Ok(self.len(storage)? == 0)
+    }
+
+    /// Binary searches for `key`, returning its index and value if present, or the index it
+    /// would be inserted at.
+    fn find<S: StorageOps>(&self, storage: &S, key: &K) -> Result<(usize, Option<V>)> {
+        let entries = self.entries();
+        let (mut low, mut high) = (0, entries.len(storage)?);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let (mid_key, value) = entries.at_unchecked(mid).read(storage)?;
+            match mid_key.cmp(key) {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => return Ok((mid, Some(value))),
+            }
+        }
+        Ok((low, None))
+    }
+
+    pub fn get<S: StorageOps>(&self, storage: &S, key: &K) -> Result<Option<V>> {
+        Ok(self.find(storage, key)?.1)
+    }
+
+    /// Inserts or updates `key`, shifting later entries up by one to keep the region sorted.
+    /// Returns `true` if the key was not present before.
+    pub fn insert<S: StorageOps>(&mut self, storage: &mut S, key: K, value: V) -> Result<bool> {
+        let (index, existing) = self.find(storage, &key)?;
+        let mut entries = self.entries();
+        if existing.is_some() {
+            entries.at_unchecked(index).write(storage, (key, value))?;
+            return Ok(false);
+        }
+
+        let mut carry = (key, value);
+        for i in index..entries.len(storage)? {
+            let mut entry = entries.at_unchecked(i);
+            let next = entry.read(storage)?;
+            entry.write(storage, carry)?;
+            carry = next;
+        }
+        entries.push(storage, carry)?;
+        Ok(true)
+    }
+
+    /// Removes `key`, shifting later entries down by one. Returns `true` if the key was present.
+    pub fn remove<S: StorageOps>(&mut self, storage: &mut S, key: &K) -> Result<bool> {
+        let (index, existing) = self.find(storage, key)?;
+        if existing.is_none() {
+            return Ok(false);
+        }
+
+        let mut entries = self.entries();
+        let last = entries.len(storage)? - 1;
+        for i in index..last {
+            let next = entries.at_unchecked(i + 1).read(storage)?;
+            entries.at_unchecked(i).write(storage, next)?;
+        }
+        entries.swap_remove(storage, last)?;
+        Ok(true)
+    }
+
+    /// Iterates over the entries in ascending key order.
+    #[inline]
+    pub fn iter<'a, S: StorageOps>(&self, storage: &'a S) -> Result<ElementIter<'a, (K, V), S>> {
+        self.entries().iter(storage)
+    }
+}
+
+impl<K, V> Handler<BTreeMap<K, V>> for OrderedMapHandler<K, V>
+where
+    K: Storable + Ord + Clone,
+    V: Storable + Clone,
+{
+    fn read<S: StorageOps>(&self, storage: &S) -> Result<BTreeMap<K, V>> {
+        Slot::new(self.base_slot).read(storage)
+    }
+
+    fn write<S: StorageOps>(&mut self, storage: &mut S, value: BTreeMap<K, V>) -> Result<()> {
+        Slot::new(self.base_slot).write(storage, value)
+    }
+
+    fn delete<S: StorageOps>(&mut self, storage: &mut S) -> Result<()> {
+        Slot::<BTreeMap<K, V>>::new(self.base_slot).delete(storage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{packing::data_region_slot, test_util::MemoryStorage};
+
+    #[test]
+    fn test_insert_out_of_order_iterates_sorted() -> Result<()> {
+        let mut storage = MemoryStorage::new();
+        let mut map = OrderedMapHandler::<U256, U256>::new(U256::from(3));
+
+        for key in [50u64, 10, 90, 30, 70] {
+            assert!(map.insert(&mut storage, U256::from(key), U256::from(key * 2))?);
+        }
+        assert!(!map.insert(&mut storage, U256::from(30), U256::from(31))?);
+
+        let entries = map.iter(&storage)?.collect::<Result<Vec<_>>>()?;
+        let keys: Vec<_> = entries.iter().map(|(key, _)| key.to::<u64>()).collect();
+        assert_eq!(keys, [10, 30, 50, 70, 90]);
+        assert_eq!(map.get(&storage, &U256::from(30))?, Some(U256::from(31)));
+        assert_eq!(map.get(&storage, &U256::from(40))?, None);
+
+        // entry i occupies two slots of the data region
+        let data = data_region_slot(U256::from(3));
+        assert_eq!(storage.load(U256::from(3))?, U256::from(5));
+        assert_eq!(storage.load(data + U256::from(4))?, U256::from(50));
+        assert_eq!(storage.load(data + U256::from(5))?, U256::from(100));
+
+        assert!(map.remove(&mut storage, &U256::from(10))?);
+        assert!(!map.remove(&mut storage, &U256::from(10))?);
+        let keys: Vec<_> = map
+            .iter(&storage)?
+            .map(|entry| entry.map(|(key, _)| key.to::<u64>()))
+            .collect::<Result<_>>()?;
+        assert_eq!(keys, [30, 50, 70, 90]);
+        assert_eq!(storage.load(data + U256::from(8))?, U256::ZERO);
+        Ok(())
+    }
+
+    #[test]
+    fn test_btree_map_round_trip() -> Result<()> {
+        let mut storage = MemoryStorage::new();
+        let mut slot = Slot::<BTreeMap<u64, U256>>::new(U256::from(7));
+        let snapshot: BTreeMap<_, _> = (0..4u64).rev().map(|i| (i, U256::from(i + 100))).collect();
+
+        slot.write(&mut storage, snapshot.clone())?;
+        assert_eq!(slot.read(&storage)?, snapshot);
+
+        let handler = OrderedMapHandler::<u64, U256>::new(U256::from(7));
+        assert_eq!(handler.get(&storage, &2)?, Some(U256::from(102)));
+
+        slot.delete(&mut storage)?;
+        assert!(handler.is_empty(&storage)?);
+        assert!(slot.read(&storage)?.is_empty());
+        Ok(())
+    }
+}
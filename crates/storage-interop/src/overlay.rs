@@ -0,0 +1,110 @@
+//! [`StorageOps`] layering pending writes over a base backend, for speculative execution.
+
+use alloy_primitives::U256;
+use std::collections::BTreeSet;
+
+use crate::{Result, storage::StorageOps};
+
+/// Storage whose writes go to `over` while reads fall back to `base` for untouched slots.
+///
+/// Written slots are tracked separately from `over`'s contents, so zeroing a slot in the overlay
+/// shadows a non-zero value in the base instead of falling through to it.
+#[derive(Debug, Clone, Default)]
+pub struct OverlayStorage<B, O> {
+    base: B,
+    over: O,
+    written: BTreeSet<U256>,
+}
+
+impl<B: StorageOps, O: StorageOps> OverlayStorage<B, O> {
+    pub fn new(base: B, over: O) -> Self {
+        Self {
+            base,
+            over,
+            written: BTreeSet::new(),
+        }
+    }
+
+    pub fn base(&self) -> &B {
+        &self.base
+    }
+
+    pub fn into_parts(self) -> (B, O) {
+        (self.base, self.over)
+    }
+
+    /// Returns the `(slot, value)` pairs written to the overlay, sorted by slot.
+    pub fn overlay_dump(&self) -> Result<Vec<(U256, U256)>> {
+        self.written
+            .iter()
+            .map(|slot| Ok((*slot, self.over.load(*slot)?)))
+            .collect()
+    }
+
+    /// Applies the overlay writes to `target`.
+    pub fn merge_into<T: StorageOps>(&self, target: &mut T) -> Result<()> {
+        for (slot, value) in self.overlay_dump()? {
+            target.store(slot, value)?;
+        }
+        Ok(())
+    }
+
+    /// Applies the overlay writes to the base and returns it.
+    pub fn commit(mut self) -> Result<B> {
+        for (slot, value) in self.overlay_dump()? {
+            self.base.store(slot, value)?;
+        }
+        Ok(self.base)
+    }
+}
+
+impl<B: StorageOps, O: StorageOps> StorageOps for OverlayStorage<B, O> {
+    fn load(&self, slot: U256) -> Result<U256> {
+        if self.written.contains(&slot) {
+            self.over.load(slot)
+        } else {
+            self.base.load(slot)
+        }
+    }
+
+    fn store(&mut self, slot: U256, value: U256) -> Result<()> {
+        self.written.insert(slot);
+        self.over.store(slot, value)
+    }
+
+    fn prefetch(&self, slots: &[U256]) -> Result<()> {
+        self.base.prefetch(slots)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Handler, VecHandler, test_util::MemoryStorage};
+
+    #[test]
+    fn test_reads_fall_through_and_writes_stay_in_overlay() -> Result<()> {
+        let mut committed = MemoryStorage::new();
+        let mut list = VecHandler::<U256>::new(U256::from(4));
+        list.write(&mut committed, vec![U256::from(1), U256::from(2)])?;
+        committed.store(U256::ONE, U256::from(7))?;
+
+        let mut overlay = OverlayStorage::new(committed.clone(), MemoryStorage::new());
+        assert_eq!(list.read(&overlay)?, [U256::from(1), U256::from(2)]);
+
+        list.push(&mut overlay, U256::from(3))?;
+        overlay.store(U256::ONE, U256::ZERO)?;
+        assert_eq!(list.read(&overlay)?.len(), 3);
+        assert_eq!(overlay.load(U256::ONE)?, U256::ZERO);
+
+        // the base is untouched until the overlay is merged
+        assert_eq!(overlay.base(), &committed);
+        assert_eq!(overlay.overlay_dump()?.len(), 3);
+
+        let mut merged = committed.clone();
+        overlay.merge_into(&mut merged)?;
+        assert_eq!(list.read(&merged)?.len(), 3);
+        assert_eq!(merged, overlay.commit()?);
+        Ok(())
+    }
+}
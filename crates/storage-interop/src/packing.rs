@@ -2,7 +2,12 @@
 
 use alloy_primitives::U256;
 
-use crate::{layout::Packable, storage::StorageOps, InteropError, Result};
+use crate::{
+    layout::{Layout, LayoutCtx, Packable, Storable},
+    storage::{Keccak, Keccak256, StorageOps},
+    InteropError,
+    Result,
+};
 
 pub struct PackedSlot(pub U256);
 
@@ -17,6 +22,46 @@ impl StorageOps for PackedSlot {
     }
 }
 
+impl PackedSlot {
+    /// Starts composing a slot from several packed fields, see [`PackedSlotBuilder`].
+    pub fn builder() -> PackedSlotBuilder {
+        PackedSlotBuilder::default()
+    }
+}
+
+/// Composes a single packed slot field by field.
+///
+/// Only the byte range of each [`FieldLocation`] is used, `offset_slots` is ignored. Values are
+/// inserted with [`insert_packed_value_checked`], so values too wide for their field are
+/// rejected rather than truncated.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PackedSlotBuilder {
+    value: U256,
+    occupied: U256,
+}
+
+impl PackedSlotBuilder {
+    /// Inserts `value` at `loc`, failing if its bytes overlap a previously added field.
+    pub fn field<T: Packable>(self, value: &T, loc: FieldLocation) -> Result<Self> {
+        let (offset, bytes) = (loc.offset_bytes, loc.size);
+        let value = insert_packed_value_checked(self.value, value, offset, bytes)?;
+
+        let mask = create_element_mask(bytes) << (offset * 8);
+        if self.occupied & mask != U256::ZERO {
+            return Err(InteropError::OverlappingPackedField { offset, bytes });
+        }
+
+        Ok(Self {
+            value,
+            occupied: self.occupied | mask,
+        })
+    }
+
+    pub fn build(self) -> U256 {
+        self.value
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct FieldLocation {
     pub offset_slots: usize,
@@ -25,6 +70,7 @@ pub struct FieldLocation {
 }
 
 impl FieldLocation {
+    /// Creates a location without validation; see [`Self::try_new`] and [`Self::checked`].
     #[inline]
     pub const fn new(offset_slots: usize, offset_bytes: usize, size: usize) -> Self {
         Self {
@@ -33,6 +79,34 @@ impl FieldLocation {
             size,
         }
     }
+
+    /// Creates a location, rejecting fields that would cross a slot boundary.
+    ///
+    /// Fields spanning whole slots (offset 0, size a multiple of 32) are accepted.
+    pub fn try_new(offset_slots: usize, offset_bytes: usize, size: usize) -> Result<Self> {
+        if !Self::fits_slot(offset_bytes, size) {
+            return Err(InteropError::PackedSlotOverflow {
+                slot: None,
+                offset: offset_bytes,
+                bytes: size,
+            });
+        }
+        Ok(Self::new(offset_slots, offset_bytes, size))
+    }
+
+    /// Like [`Self::try_new`], but panics on an invalid location, which fails compilation when
+    /// used in a `const`.
+    pub const fn checked(offset_slots: usize, offset_bytes: usize, size: usize) -> Self {
+        assert!(
+            Self::fits_slot(offset_bytes, size),
+            "field location crosses a slot boundary"
+        );
+        Self::new(offset_slots, offset_bytes, size)
+    }
+
+    const fn fits_slot(offset_bytes: usize, size: usize) -> bool {
+        offset_bytes + size <= 32 || (offset_bytes == 0 && size.is_multiple_of(32))
+    }
 }
 
 #[inline]
@@ -51,7 +125,11 @@ pub fn extract_packed_value<T: Packable>(
     bytes: usize,
 ) -> Result<T> {
     if offset + bytes > 32 {
-        return Err(InteropError::PackedSlotOverflow { offset, bytes });
+        return Err(InteropError::PackedSlotOverflow {
+            slot: None,
+            offset,
+            bytes,
+        });
     }
 
     let shift_bits = offset * 8;
@@ -68,7 +146,11 @@ pub fn insert_packed_value<T: Packable>(
     bytes: usize,
 ) -> Result<U256> {
     if offset + bytes > 32 {
-        return Err(InteropError::PackedSlotOverflow { offset, bytes });
+        return Err(InteropError::PackedSlotOverflow {
+            slot: None,
+            offset,
+            bytes,
+        });
     }
 
     let field_value = value.to_word();
@@ -81,10 +163,39 @@ pub fn insert_packed_value<T: Packable>(
     Ok(cleared | positioned)
 }
 
+/// Checked variant of [`insert_packed_value`] that fails with [`InteropError::ValueTooWide`]
+/// instead of silently truncating values that don't fit in `bytes`.
+///
+/// A value fits if decoding its truncated word yields the same value, which accepts
+/// sign-extended negative numbers for signed types.
+#[inline]
+pub fn insert_packed_value_checked<T: Packable>(
+    current: U256,
+    value: &T,
+    offset: usize,
+    bytes: usize,
+) -> Result<U256> {
+    let word = value.to_word();
+    let truncated = word & create_element_mask(bytes);
+    let fits = truncated == word || T::from_word(truncated).is_ok_and(|v| v.to_word() == word);
+    if !fits {
+        return Err(InteropError::ValueTooWide {
+            value_bits: word.bit_len(),
+            field_bytes: bytes,
+        });
+    }
+
+    insert_packed_value(current, value, offset, bytes)
+}
+
 #[inline]
 pub fn zero_packed_value(current: U256, offset: usize, bytes: usize) -> Result<U256> {
     if offset + bytes > 32 {
-        return Err(InteropError::PackedSlotOverflow { offset, bytes });
+        return Err(InteropError::PackedSlotOverflow {
+            slot: None,
+            offset,
+            bytes,
+        });
     }
 
     let mask = create_element_mask(bytes);
@@ -92,14 +203,23 @@ pub fn zero_packed_value(current: U256, offset: usize, bytes: usize) -> Result<U
     Ok(current & !shifted_mask)
 }
 
+/// Number of `elem_bytes`-wide elements packed into one slot.
+///
+/// Solidity never lets an element straddle two slots, so when the width does not divide 32
+/// (e.g. `uint24`) the remaining bytes of each slot are left unused.
+#[inline]
+pub const fn elements_per_slot(elem_bytes: usize) -> usize {
+    32 / elem_bytes
+}
+
 #[inline]
 pub const fn calc_element_slot(idx: usize, elem_bytes: usize) -> usize {
-    (idx * elem_bytes) / 32
+    idx / elements_per_slot(elem_bytes)
 }
 
 #[inline]
 pub const fn calc_element_offset(idx: usize, elem_bytes: usize) -> usize {
-    (idx * elem_bytes) % 32
+    (idx % elements_per_slot(elem_bytes)) * elem_bytes
 }
 
 #[inline]
@@ -111,7 +231,364 @@ pub const fn calc_element_loc(idx: usize, elem_bytes: usize) -> FieldLocation {
     )
 }
 
+/// Largest length accepted when decoding the header of a dynamic value (`Vec`, `BitVec`,
+/// `Bytes`, `String`).
+///
+/// Anything longer could never have been written within a block's gas, so larger headers are
+/// treated as corrupt instead of driving huge allocations or overflowing `usize`.
+pub const MAX_DYNAMIC_LENGTH: usize = u32::MAX as usize;
+
+/// Most elements (or slots, or bytes) reserved up front while decoding a dynamic value.
+///
+/// A length below [`MAX_DYNAMIC_LENGTH`] can still be far larger than the data actually stored,
+/// so decoders reserve at most this much and let buffers grow as the data slots are read.
+pub(crate) const PREALLOCATION_LIMIT: usize = 1024;
+
+/// Decodes a length read from a dynamic value's header, failing with
+/// [`InteropError::InvalidLength`] above [`MAX_DYNAMIC_LENGTH`].
+#[inline]
+pub fn decode_length(claimed: U256) -> Result<usize> {
+    decode_length_with_max(claimed, MAX_DYNAMIC_LENGTH)
+}
+
+/// Like [`decode_length`] with a caller-chosen limit, for decoding untrusted dumps under a
+/// tighter bound.
+#[inline]
+pub fn decode_length_with_max(claimed: U256, max: usize) -> Result<usize> {
+    if claimed > U256::from(max) {
+        return Err(InteropError::InvalidLength { claimed, max });
+    }
+    Ok(claimed.to())
+}
+
+/// Slot offset of element `index` for elements spanning `slots` slots each, computed in `U256`
+/// so huge indices can't overflow `usize`.
+#[inline]
+pub(crate) fn element_slot_offset(index: usize, slots: usize) -> U256 {
+    U256::from(index) * U256::from(slots)
+}
+
 #[inline]
 pub const fn calc_packed_slot_count(n: usize, elem_bytes: usize) -> usize {
-    (n * elem_bytes).div_ceil(32)
+    n.div_ceil(elements_per_slot(elem_bytes))
+}
+
+/// Writes element `index` of a packed array whose data starts at `data_start`, preserving the
+/// other elements sharing its slot with a single load and store.
+///
+/// Only valid for elements of at most 16 bytes, which are packed [`elements_per_slot`] per slot.
+pub fn write_packed_element<T: Storable, S: StorageOps>(
+    storage: &mut S,
+    data_start: U256,
+    index: usize,
+    byte_count: usize,
+    value: &T,
+) -> Result<()> {
+    debug_assert!(
+        byte_count <= 16,
+        "elements larger than 16 bytes are not packed"
+    );
+
+    let loc = calc_element_loc(index, byte_count);
+    let slot = data_start + U256::from(loc.offset_slots);
+    let mut word = PackedSlot(storage.load(slot)?);
+    value
+        .store(&mut word, U256::ZERO, LayoutCtx::packed(loc.offset_bytes))
+        .map_err(|err| err.with_slot(slot))?;
+    storage.store(slot, word.0)
+}
+
+/// Returns the first slot of the data region of a dynamic value (`bytes`, `string` or `T[]`)
+/// whose length or header word is stored at `base_slot`, i.e. `keccak256(base_slot)`.
+///
+/// For nested layouts `base_slot` is the already resolved slot of the dynamic value itself, e.g.
+/// the mapping value slot for a `mapping(address => uint64[])`.
+#[inline]
+pub fn data_region_slot(base_slot: U256) -> U256 {
+    data_region_slot_with::<Keccak256>(base_slot)
+}
+
+/// Like [`data_region_slot`], hashing `base_slot` with `H`.
+#[inline]
+pub fn data_region_slot_with<H: Keccak>(base_slot: U256) -> U256 {
+    U256::from_be_bytes(H::hash(&base_slot.to_be_bytes::<32>()).0)
+}
+
+/// Returns the slot and byte offset of element `index` of a fixed-size array starting at
+/// `base_slot`, applying the same packing rules as `ArrayHandler`.
+///
+/// Elements of at most 16 bytes are packed; larger elements start at a slot boundary and
+/// occupy `elem_bytes.div_ceil(32)` slots each.
+#[inline]
+pub fn fixed_array_element_slot(base_slot: U256, index: usize, elem_bytes: usize) -> (U256, usize) {
+    if elem_bytes <= 16 {
+        let loc = calc_element_loc(index, elem_bytes);
+        (base_slot + U256::from(loc.offset_slots), loc.offset_bytes)
+    } else {
+        let slot = base_slot + element_slot_offset(index, elem_bytes.div_ceil(32));
+        (slot, 0)
+    }
+}
+
+/// Lays out consecutive fields following Solidity's struct packing rules.
+///
+/// Packable fields are placed back to back and moved to the next slot when they would cross
+/// a slot boundary. Non-packable fields always start a new slot, and the field after them
+/// does too. Returns the field locations together with the total number of slots used.
+const fn pack_fields<const N: usize>(layouts: [Layout; N]) -> ([FieldLocation; N], usize) {
+    let mut locs = [FieldLocation::new(0, 0, 0); N];
+    let (mut slot, mut offset) = (0, 0);
+
+    let mut i = 0;
+    while i < N {
+        match layouts[i] {
+            Layout::Bytes(bytes) => {
+                if offset + bytes > 32 {
+                    slot += 1;
+                    offset = 0;
+                }
+                locs[i] = FieldLocation::new(slot, offset, bytes);
+                offset += bytes;
+            }
+            Layout::Slots(n) => {
+                if offset != 0 {
+                    slot += 1;
+                    offset = 0;
+                }
+                locs[i] = FieldLocation::new(slot, 0, n * 32);
+                slot += n;
+            }
+        }
+        i += 1;
+    }
+
+    if offset != 0 {
+        slot += 1;
+    }
+    (locs, slot)
+}
+
+/// Lays out value-type fields of the given byte sizes following Solidity's struct packing
+/// rules, for defining `FieldLocation` constants from a single list of sizes.
+///
+/// Every size must be between 1 and 32 bytes.
+pub const fn layout_of<const N: usize>(field_bytes: [usize; N]) -> [FieldLocation; N] {
+    let mut layouts = [Layout::Bytes(0); N];
+
+    let mut i = 0;
+    while i < N {
+        assert!(
+            field_bytes[i] > 0 && field_bytes[i] <= 32,
+            "field sizes must be between 1 and 32 bytes"
+        );
+        layouts[i] = Layout::Bytes(field_bytes[i]);
+        i += 1;
+    }
+    pack_fields(layouts).0
+}
+
+#[inline]
+pub const fn calc_field_locs<const N: usize>(layouts: [Layout; N]) -> [FieldLocation; N] {
+    pack_fields(layouts).0
+}
+
+#[inline]
+pub const fn calc_struct_slots<const N: usize>(layouts: [Layout; N]) -> usize {
+    pack_fields(layouts).1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Mapping, storage::StorageKey, test_util::CountingStorage};
+    use alloy_primitives::{Address, b256, keccak256};
+
+    const fn same_loc(loc: FieldLocation, offset_slots: usize, offset_bytes: usize) -> bool {
+        loc.offset_slots == offset_slots && loc.offset_bytes == offset_bytes
+    }
+
+    #[test]
+    fn test_field_location_validation() {
+        const OWNER: FieldLocation = FieldLocation::checked(0, 12, 20);
+        assert_eq!((OWNER.offset_bytes, OWNER.size), (12, 20));
+
+        let loc = FieldLocation::try_new(1, 24, 8).unwrap();
+        assert!(same_loc(loc, 1, 24));
+        assert!(FieldLocation::try_new(2, 0, 64).is_ok());
+
+        assert!(matches!(
+            FieldLocation::try_new(0, 30, 8),
+            Err(InteropError::PackedSlotOverflow {
+                slot: None,
+                offset: 30,
+                bytes: 8
+            })
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "field location crosses a slot boundary")]
+    fn test_checked_field_location_panics() {
+        FieldLocation::checked(0, 30, 8);
+    }
+
+    #[test]
+    fn test_element_loc_skips_unused_slot_tail() {
+        // ten uint24s fill 30 bytes, the 11th starts the next slot
+        assert!(same_loc(calc_element_loc(9, 3), 0, 27));
+        assert!(same_loc(calc_element_loc(10, 3), 1, 0));
+        assert_eq!(calc_packed_slot_count(21, 3), 3);
+        assert_eq!(calc_packed_slot_count(20, 3), 2);
+
+        for elem_bytes in 1..=16 {
+            for idx in 0..64 {
+                let loc = calc_element_loc(idx, elem_bytes);
+                assert!(loc.offset_bytes + elem_bytes <= 32);
+                assert!(loc.offset_slots < calc_packed_slot_count(idx + 1, elem_bytes));
+            }
+        }
+    }
+
+    #[test]
+    fn test_packed_slot_builder_matches_manual_encoding() -> Result<()> {
+        // TIP403 `PolicyData { policy_type: u8, admin: Address }`
+        const LOCS: [FieldLocation; 2] = layout_of([1, 20]);
+        let [policy_type_loc, admin_loc] = LOCS;
+        let (policy_type, admin) = (2u8, Address::repeat_byte(0xab));
+
+        let manual = insert_packed_value(
+            U256::ZERO,
+            &policy_type,
+            policy_type_loc.offset_bytes,
+            policy_type_loc.size,
+        )?;
+        let manual = insert_packed_value(manual, &admin, admin_loc.offset_bytes, admin_loc.size)?;
+
+        let built = PackedSlot::builder()
+            .field(&policy_type, policy_type_loc)?
+            .field(&admin, admin_loc)?
+            .build();
+        assert_eq!(built, manual);
+
+        let mut stored = PackedSlot(U256::ZERO);
+        (policy_type, admin).store(&mut stored, U256::ZERO, LayoutCtx::FULL)?;
+        assert_eq!(built, stored.0);
+
+        assert!(matches!(
+            PackedSlot::builder()
+                .field(&admin, admin_loc)?
+                .field(&1u32, FieldLocation::new(0, 18, 4)),
+            Err(InteropError::OverlappingPackedField {
+                offset: 18,
+                bytes: 4
+            })
+        ));
+        assert!(matches!(
+            PackedSlot::builder().field(&0x1ffu16, FieldLocation::new(0, 0, 1)),
+            Err(InteropError::ValueTooWide { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_layout_of() {
+        const {
+            // uint8, address: both fit in slot 0
+            let locs = layout_of([1, 20]);
+            assert!(same_loc(locs[0], 0, 0) && same_loc(locs[1], 0, 1));
+            assert!(locs[1].size == 20);
+
+            // uint128, uint128, uint8: the third spills into slot 1
+            let locs = layout_of([16, 16, 1]);
+            assert!(same_loc(locs[1], 0, 16) && same_loc(locs[2], 1, 0));
+
+            // uint8, uint256, uint8: full-width fields never share a slot
+            let locs = layout_of([1, 32, 1]);
+            assert!(same_loc(locs[1], 1, 0) && same_loc(locs[2], 2, 0));
+
+            // address, uint64, uint32: exactly 32 bytes, no spill
+            let locs = layout_of([20, 8, 4]);
+            assert!(same_loc(locs[2], 0, 28));
+        }
+    }
+
+    #[test]
+    fn test_data_region_slot() {
+        let expected = b256!("0x290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563");
+        assert_eq!(
+            data_region_slot(U256::ZERO),
+            U256::from_be_bytes(expected.0)
+        );
+    }
+
+    #[test]
+    fn test_mapping_of_vec_element_slots() {
+        // mapping(address => uint64[]) at slot 3
+        let key = Address::repeat_byte(0x5a);
+        let mut preimage = [0u8; 64];
+        preimage[12..32].copy_from_slice(key.as_slice());
+        preimage[63] = 3;
+        let len_slot = U256::from_be_bytes(keccak256(preimage).0);
+        assert_eq!(key.mapping_slot(U256::from(3)), len_slot);
+
+        let data_start = U256::from_be_bytes(keccak256(len_slot.to_be_bytes::<32>()).0);
+        let handler = Mapping::<Address, Vec<u64>>::new(U256::from(3)).at(key);
+        assert_eq!(handler.data_slot(), data_start);
+
+        // four uint64 per slot
+        for (index, slot, offset) in [(0, 0, 0), (3, 0, 24), (5, 1, 8)] {
+            let (elem_slot, ctx) = handler.element_location(index);
+            assert_eq!(elem_slot, data_start + U256::from(slot));
+            assert_eq!(ctx.packed_offset(), Some(offset));
+        }
+    }
+
+    #[test]
+    fn test_insert_packed_value_checked() {
+        assert_eq!(
+            insert_packed_value(U256::ZERO, &300u16, 0, 1).unwrap(),
+            U256::from(44)
+        );
+        assert!(matches!(
+            insert_packed_value_checked(U256::ZERO, &300u16, 0, 1),
+            Err(InteropError::ValueTooWide {
+                value_bits: 9,
+                field_bytes: 1
+            })
+        ));
+
+        assert_eq!(
+            insert_packed_value_checked(U256::ZERO, &255u16, 2, 1).unwrap(),
+            U256::from(0xff0000)
+        );
+        assert!(insert_packed_value_checked(U256::ZERO, &-1i64, 0, 8).is_ok());
+        assert!(insert_packed_value_checked(U256::ZERO, &-1i64, 0, 4).is_err());
+    }
+
+    #[test]
+    fn test_write_packed_element_preserves_neighbours() -> Result<()> {
+        let mut storage = CountingStorage::default();
+        let data_start = U256::from(50);
+        // a full slot of eight uint32s
+        let full = U256::from_be_bytes([0x11; 32]);
+        storage.store(data_start, full)?;
+        storage.reset();
+
+        write_packed_element(&mut storage, data_start, 4, 4, &0xdeadbeef_u32)?;
+        assert_eq!((storage.loads(), storage.stores()), (1, 1));
+
+        let word = storage.load(data_start)?;
+        assert_eq!(extract_packed_value::<u32>(word, 16, 4)?, 0xdeadbeef);
+        for neighbour in [3, 5] {
+            assert_eq!(
+                extract_packed_value::<u32>(word, neighbour * 4, 4)?,
+                0x11111111
+            );
+        }
+        assert_eq!(
+            zero_packed_value(word, 16, 4)?,
+            zero_packed_value(full, 16, 4)?
+        );
+        Ok(())
+    }
 }
@@ -1,11 +1,19 @@
 pub mod runtime_context;
 pub mod runtime_provider;
 pub mod runtime_storage_ops;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod runtime_mock;
 #[cfg(feature = "revm")]
 pub mod runtime_revm;
 
 pub use runtime_context::RuntimeContext;
-pub use runtime_provider::PrecompileStorageProvider;
-pub use runtime_storage_ops::{RuntimeStorageOps, StorageMode};
+pub use runtime_provider::{
+    CallOutcome, MAX_LOG_TOPICS, PrecompileFn, PrecompileRegistry, PrecompileStorageProvider,
+};
+#[cfg(any(test, feature = "test-utils"))]
+pub use runtime_mock::{MockAccount, MockProvider};
+pub use runtime_storage_ops::{
+    DualStorageOps, RuntimeStorageOps, StorageBatch, StorageMode, StorageView,
+};
 #[cfg(feature = "revm")]
 pub use runtime_revm::RevmStorageProvider;
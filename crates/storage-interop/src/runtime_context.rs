@@ -1,8 +1,10 @@
-use alloy_primitives::Address;
+use alloy_primitives::{Address, B256, Bytes, U256};
 
 use crate::{
-    runtime_provider::PrecompileStorageProvider,
-    runtime_storage_ops::{RuntimeStorageOps, StorageMode},
+    Result,
+    event::EventBuilder,
+    runtime_provider::{CallOutcome, PrecompileStorageProvider},
+    runtime_storage_ops::{DualStorageOps, RuntimeStorageOps, StorageMode, StorageView},
 };
 
 pub struct RuntimeContext<'a, P> {
@@ -22,6 +24,22 @@ where
         self.address
     }
 
+    pub fn block_number(&self) -> U256 {
+        self.provider.block_number()
+    }
+
+    pub fn block_hash(&self, number: U256) -> Result<B256> {
+        self.provider.block_hash(number)
+    }
+
+    pub fn balance(&self, address: Address) -> Result<U256> {
+        self.provider.balance(address)
+    }
+
+    pub fn nonce(&self, address: Address) -> Result<u64> {
+        self.provider.nonce(address)
+    }
+
     pub fn provider(&mut self) -> &mut P {
         self.provider
     }
@@ -33,4 +51,106 @@ where
     pub fn transient_ops(&mut self) -> RuntimeStorageOps<'_, P> {
         RuntimeStorageOps::new(self.provider, self.address, StorageMode::Transient)
     }
+
+    /// Persistent storage ops targeting `other` instead of this contract, e.g. to read a
+    /// registry contract's state.
+    ///
+    /// Writes through these ops modify the foreign contract's storage directly, bypassing any of
+    /// its own invariants, so they should be reserved for state this precompile owns.
+    pub fn ops_for(&mut self, other: Address) -> RuntimeStorageOps<'_, P> {
+        RuntimeStorageOps::new(self.provider, other, StorageMode::Persistent)
+    }
+
+    pub fn dual_ops(&mut self) -> DualStorageOps<'_, P> {
+        DualStorageOps::new(self.provider, self.address)
+    }
+
+    /// Runs `f` with persistent and transient views of this contract's storage.
+    pub fn with_both<R>(
+        &mut self,
+        f: impl FnOnce(&mut StorageView<'_, '_, P>, &mut StorageView<'_, '_, P>) -> Result<R>,
+    ) -> Result<R> {
+        let dual = self.dual_ops();
+        f(&mut dual.persistent(), &mut dual.transient())
+    }
+
+    /// Deploys `init_code` with CREATE2 from the provider's caller, which is expected to be this
+    /// contract.
+    pub fn create2(
+        &mut self,
+        salt: B256,
+        init_code: Bytes,
+        value: U256,
+        gas: u64,
+    ) -> Result<(Address, CallOutcome)> {
+        self.provider.create2(salt, init_code, value, gas)
+    }
+
+    pub fn emit_event(&mut self, event: EventBuilder) -> Result<()> {
+        self.provider.emit_event(self.address, event.build())
+    }
+
+    /// ABI-encodes `event` and emits it from this contract, like Solidity's `emit`.
+    #[cfg(feature = "sol-types")]
+    pub fn emit<E: alloy_sol_types::SolEvent>(&mut self, event: E) -> Result<()> {
+        self.provider
+            .emit_event(self.address, event.encode_log_data())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "sol-types")]
+    use alloy_sol_types::{SolEvent, sol};
+
+    use super::*;
+    use crate::{Handler, MockProvider, Slot, storage::StorageOps};
+
+    #[cfg(feature = "sol-types")]
+    sol! {
+        event Transfer(address indexed from, address indexed to, uint256 value);
+    }
+
+    #[test]
+    fn test_ops_for_address() -> Result<()> {
+        let mut provider = MockProvider::new();
+        let (own, registry) = (Address::repeat_byte(0x0a), Address::repeat_byte(0x0b));
+        let mut ctx = RuntimeContext::new(&mut provider, own);
+
+        let mut slot = Slot::<U256>::new(U256::from(3));
+        slot.write(&mut ctx.storage_ops(), U256::from(99))?;
+        assert_eq!(slot.read(&ctx.ops_for(own))?, U256::from(99));
+        assert_eq!(slot.read(&ctx.ops_for(registry))?, U256::ZERO);
+
+        ctx.ops_for(registry).store(U256::ONE, U256::from(5))?;
+        assert_eq!(ctx.ops_for(registry).address(), registry);
+        assert_eq!(provider.sload(registry, U256::ONE)?, U256::from(5));
+        assert_eq!(provider.sload(own, U256::ONE)?, U256::ZERO);
+        Ok(())
+    }
+
+    #[cfg(feature = "sol-types")]
+    #[test]
+    fn test_emit_typed_event() -> Result<()> {
+        let mut provider = MockProvider::new();
+        let token = Address::repeat_byte(0x10);
+        let (from, to) = (Address::repeat_byte(0x01), Address::repeat_byte(0x02));
+
+        RuntimeContext::new(&mut provider, token).emit(Transfer {
+            from,
+            to,
+            value: U256::from(500),
+        })?;
+
+        let log = &provider.logs()[0];
+        assert_eq!(log.address, token);
+        assert_eq!(log.topics()[0], Transfer::SIGNATURE_HASH);
+        assert_eq!(
+            log.topics()[0],
+            alloy_primitives::keccak256("Transfer(address,address,uint256)")
+        );
+        assert_eq!(log.topics()[1], from.into_word());
+        assert_eq!(log.data.data, U256::from(500).to_be_bytes::<32>().to_vec());
+        Ok(())
+    }
 }
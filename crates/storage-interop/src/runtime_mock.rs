@@ -0,0 +1,578 @@
+//! In-memory [`PrecompileStorageProvider`] for unit-testing precompile logic without revm.
+//!
+//! ```
+//! use alloy_primitives::{Address, U256};
+//! use tempo_storage_interop::{Handler, MockProvider, RuntimeContext, Slot};
+//!
+//! # fn main() -> tempo_storage_interop::Result<()> {
+//! let mut provider = MockProvider::new().with_chain_id(42);
+//!
+//! let contract = Address::repeat_byte(0xc0);
+//! let mut runtime = RuntimeContext::new(&mut provider, contract);
+//! let mut ops = runtime.storage_ops();
+//!
+//! let mut slot = Slot::<U256>::new(U256::from(0));
+//! slot.write(&mut ops, U256::from(42))?;
+//! assert_eq!(slot.read(&ops)?, U256::from(42));
+//!
+//! assert_eq!(
+//!     provider.storage_dump(),
+//!     vec![(contract, U256::ZERO, U256::from(42))]
+//! );
+//! # Ok(())
+//! # }
+//! ```
+
+use alloy_primitives::{Address, B256, Bytes, Log, LogData, U256};
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    InteropError, Result,
+    runtime_provider::{
+        CallOutcome, MAX_LOG_TOPICS, PrecompileFn, PrecompileRegistry, PrecompileStorageProvider,
+    },
+};
+
+/// Account state tracked by [`MockProvider`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MockAccount {
+    pub balance: U256,
+    pub nonce: u64,
+    pub code: Bytes,
+}
+
+/// [`PrecompileStorageProvider`] backed by in-memory maps.
+///
+/// Storage accesses are free; only `deduct_gas` and `call_precompile` count against the gas
+/// limit, which defaults to `u64::MAX`.
+#[derive(Debug, Clone)]
+pub struct MockProvider {
+    chain_id: u64,
+    timestamp: U256,
+    beneficiary: Address,
+    block_number: U256,
+    caller: Option<Address>,
+    is_static: bool,
+    storage: HashMap<(Address, U256), U256>,
+    sstore_count: usize,
+    transient: HashMap<(Address, U256), U256>,
+    accounts: HashMap<Address, MockAccount>,
+    created: HashSet<Address>,
+    eip6780: bool,
+    block_hashes: HashMap<U256, B256>,
+    logs: Vec<Log>,
+    gas_limit: u64,
+    gas_used: u64,
+    gas_refunded: i64,
+    precompiles: PrecompileRegistry,
+}
+
+impl Default for MockProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockProvider {
+    pub fn new() -> Self {
+        Self {
+            chain_id: 1,
+            timestamp: U256::ZERO,
+            beneficiary: Address::ZERO,
+            block_number: U256::ZERO,
+            caller: None,
+            is_static: false,
+            storage: HashMap::new(),
+            sstore_count: 0,
+            transient: HashMap::new(),
+            accounts: HashMap::new(),
+            created: HashSet::new(),
+            eip6780: true,
+            block_hashes: HashMap::new(),
+            logs: Vec::new(),
+            gas_limit: u64::MAX,
+            gas_used: 0,
+            gas_refunded: 0,
+            precompiles: PrecompileRegistry::new(),
+        }
+    }
+
+    pub fn with_chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    pub fn with_timestamp(mut self, timestamp: U256) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    pub fn with_beneficiary(mut self, beneficiary: Address) -> Self {
+        self.beneficiary = beneficiary;
+        self
+    }
+
+    pub fn with_block_number(mut self, block_number: U256) -> Self {
+        self.block_number = block_number;
+        self
+    }
+
+    pub fn with_static(mut self, is_static: bool) -> Self {
+        self.is_static = is_static;
+        self
+    }
+
+    pub fn with_gas_limit(mut self, gas_limit: u64) -> Self {
+        self.gas_limit = gas_limit;
+        self
+    }
+
+    /// Toggles the post-Cancun `destroy_account` semantics (EIP-6780), enabled by default.
+    pub fn with_eip6780(mut self, enabled: bool) -> Self {
+        self.eip6780 = enabled;
+        self
+    }
+
+    /// Sets the address `create2` deploys from.
+    pub fn set_caller(&mut self, caller: Address) {
+        self.caller = Some(caller);
+    }
+
+    /// Sets the hash returned by `block_hash(number)`, subject to the usual 256-block window.
+    pub fn set_block_hash(&mut self, number: U256, hash: B256) {
+        self.block_hashes.insert(number, hash);
+    }
+
+    pub fn set_account(&mut self, address: Address, account: MockAccount) {
+        self.accounts.insert(address, account);
+    }
+
+    pub fn account(&self, address: Address) -> Option<&MockAccount> {
+        self.accounts.get(&address)
+    }
+
+    pub fn register_precompile(&mut self, id: Address, precompile: PrecompileFn) {
+        self.precompiles.register(id, precompile);
+    }
+
+    /// Returns every emitted log in emission order.
+    pub fn logs(&self) -> &[Log] {
+        &self.logs
+    }
+
+    /// Number of `sstore` calls so far, including ones that leave the slot unchanged.
+    pub fn sstore_count(&self) -> usize {
+        self.sstore_count
+    }
+
+    /// Returns all non-zero persistent `(address, slot, value)` entries, sorted.
+    pub fn storage_dump(&self) -> Vec<(Address, U256, U256)> {
+        let mut dump: Vec<_> = self
+            .storage
+            .iter()
+            .filter(|(_, value)| !value.is_zero())
+            .map(|((address, slot), value)| (*address, *slot, *value))
+            .collect();
+        dump.sort();
+        dump
+    }
+}
+
+impl PrecompileStorageProvider for MockProvider {
+    type AccountInfo = MockAccount;
+    type Bytecode = Bytes;
+    type Spec = ();
+
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    fn timestamp(&self) -> U256 {
+        self.timestamp
+    }
+
+    fn beneficiary(&self) -> Address {
+        self.beneficiary
+    }
+
+    fn block_number(&self) -> U256 {
+        self.block_number
+    }
+
+    fn block_hash(&self, number: U256) -> Result<B256> {
+        let current = self.block_number;
+        let in_range = number < current && current - number <= U256::from(256);
+        if !in_range {
+            return Ok(B256::ZERO);
+        }
+        Ok(self.block_hashes.get(&number).copied().unwrap_or_default())
+    }
+
+    fn is_static(&self) -> bool {
+        self.is_static
+    }
+
+    fn sload(&self, address: Address, slot: U256) -> Result<U256> {
+        Ok(self
+            .storage
+            .get(&(address, slot))
+            .copied()
+            .unwrap_or_default())
+    }
+
+    fn sstore(&mut self, address: Address, slot: U256, value: U256) -> Result<()> {
+        self.sstore_count += 1;
+        self.storage.insert((address, slot), value);
+        Ok(())
+    }
+
+    fn tload(&self, address: Address, slot: U256) -> Result<U256> {
+        Ok(self
+            .transient
+            .get(&(address, slot))
+            .copied()
+            .unwrap_or_default())
+    }
+
+    fn tstore(&mut self, address: Address, slot: U256, value: U256) -> Result<()> {
+        self.transient.insert((address, slot), value);
+        Ok(())
+    }
+
+    fn warm_account(&mut self, _address: Address) -> Result<()> {
+        Ok(())
+    }
+
+    fn warm_slot(&mut self, _address: Address, _slot: U256) -> Result<()> {
+        Ok(())
+    }
+
+    fn balance(&self, address: Address) -> Result<U256> {
+        Ok(self
+            .accounts
+            .get(&address)
+            .map(|account| account.balance)
+            .unwrap_or_default())
+    }
+
+    fn nonce(&self, address: Address) -> Result<u64> {
+        Ok(self
+            .accounts
+            .get(&address)
+            .map(|account| account.nonce)
+            .unwrap_or_default())
+    }
+
+    fn set_code(&mut self, address: Address, code: Bytes) -> Result<()> {
+        self.accounts.entry(address).or_default().code = code;
+        Ok(())
+    }
+
+    fn with_account_info(
+        &mut self,
+        address: Address,
+        f: &mut dyn FnMut(&MockAccount),
+    ) -> Result<()> {
+        f(self.accounts.entry(address).or_default());
+        Ok(())
+    }
+
+    fn emit_event(&mut self, address: Address, log: LogData) -> Result<()> {
+        let count = log.topics().len();
+        if count > MAX_LOG_TOPICS {
+            return Err(InteropError::TooManyLogTopics { count });
+        }
+        self.logs.push(Log { address, data: log });
+        Ok(())
+    }
+
+    fn call_precompile(
+        &mut self,
+        id: Address,
+        input: Bytes,
+        gas: u64,
+    ) -> Result<(bool, Bytes, u64)> {
+        let (success, output, gas_used) = self.precompiles.call(id, &input, gas)?;
+        self.deduct_gas(gas_used)?;
+        Ok((success, output, gas_used))
+    }
+
+    /// Dispatches to registered precompiles; calls to any other address succeed with empty
+    /// output. Value is checked against the static flag but not transferred.
+    fn call(&mut self, to: Address, input: Bytes, value: U256, gas: u64) -> Result<CallOutcome> {
+        if self.is_static && !value.is_zero() {
+            return Err(InteropError::StaticValueTransfer);
+        }
+        if !self.precompiles.contains(&to) {
+            return Ok(CallOutcome {
+                success: true,
+                output: Bytes::new(),
+                gas_used: 0,
+            });
+        }
+
+        let (success, output, gas_used) = self.call_precompile(to, input, gas)?;
+        Ok(CallOutcome {
+            success,
+            output,
+            gas_used,
+        })
+    }
+
+    /// Creates the account with nonce 1 and moves `value` from the caller. The init code is not
+    /// executed, so the new account has no code.
+    fn create2(
+        &mut self,
+        salt: B256,
+        init_code: Bytes,
+        value: U256,
+        _gas: u64,
+    ) -> Result<(Address, CallOutcome)> {
+        if self.is_static {
+            return Err(InteropError::StaticCreate);
+        }
+
+        let sender = self.caller.ok_or(InteropError::MissingCaller)?;
+        let address = sender.create2_from_code(salt, init_code);
+        let mut outcome = CallOutcome {
+            success: false,
+            output: Bytes::new(),
+            gas_used: 0,
+        };
+
+        let collides = self
+            .accounts
+            .get(&address)
+            .is_some_and(|account| account.nonce != 0 || !account.code.is_empty());
+        if collides || self.balance(sender)? < value {
+            return Ok((address, outcome));
+        }
+
+        self.accounts.entry(sender).or_default().balance -= value;
+        let account = self.accounts.entry(address).or_default();
+        account.nonce = 1;
+        account.balance += value;
+        self.created.insert(address);
+        outcome.success = true;
+        Ok((address, outcome))
+    }
+
+    fn destroy_account(&mut self, address: Address, beneficiary: Address) -> Result<()> {
+        if self.is_static {
+            return Err(InteropError::StaticSelfDestruct);
+        }
+
+        let delete = !self.eip6780 || self.created.contains(&address);
+        // Sending the balance to itself keeps it, unless the account is deleted
+        if address == beneficiary && !delete {
+            return Ok(());
+        }
+
+        let balance = self.balance(address)?;
+        self.accounts.entry(address).or_default().balance = U256::ZERO;
+        if address != beneficiary {
+            self.accounts.entry(beneficiary).or_default().balance += balance;
+        }
+
+        if delete {
+            self.accounts.remove(&address);
+            self.storage.retain(|(owner, _), _| *owner != address);
+        }
+        Ok(())
+    }
+
+    fn deduct_gas(&mut self, gas: u64) -> Result<()> {
+        if gas > self.gas_remaining() {
+            return Err(InteropError::OutOfGas);
+        }
+        self.gas_used += gas;
+        Ok(())
+    }
+
+    fn refund_gas(&mut self, gas: i64) {
+        self.gas_refunded = self.gas_refunded.saturating_add(gas);
+    }
+
+    fn gas_used(&self) -> u64 {
+        self.gas_used
+    }
+
+    fn gas_remaining(&self) -> u64 {
+        self.gas_limit - self.gas_used
+    }
+
+    fn gas_refunded(&self) -> i64 {
+        self.gas_refunded
+    }
+
+    fn spec(&self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::keccak256;
+
+    use crate::{EventBuilder, Handler, Mapping, runtime_context::RuntimeContext};
+
+    #[test]
+    fn test_runtime_context_flow() -> Result<()> {
+        let contract = Address::repeat_byte(0xc0);
+        let holder = Address::repeat_byte(0x01);
+        let mut provider = MockProvider::new().with_gas_limit(1_000);
+        provider.set_account(
+            holder,
+            MockAccount {
+                balance: U256::from(5),
+                nonce: 2,
+                ..Default::default()
+            },
+        );
+
+        let mut ctx = RuntimeContext::new(&mut provider, contract);
+        assert_eq!(ctx.balance(holder)?, U256::from(5));
+        assert_eq!(ctx.nonce(holder)?, 2);
+
+        let balances = Mapping::<Address, U256>::new(U256::from(3));
+        balances
+            .at(holder)
+            .write(&mut ctx.storage_ops(), U256::from(100))?;
+        ctx.emit_event(EventBuilder::new("Touched(address)").indexed(&holder))?;
+
+        ctx.provider().deduct_gas(600)?;
+        assert!(matches!(
+            ctx.provider().deduct_gas(401),
+            Err(InteropError::OutOfGas)
+        ));
+        assert_eq!(ctx.provider().gas_remaining(), 400);
+
+        assert_eq!(provider.storage_dump().len(), 1);
+        assert_eq!(provider.logs().len(), 1);
+        assert_eq!(provider.logs()[0].address, contract);
+        assert_eq!(provider.logs()[0].topics()[1], holder.into_word());
+
+        let log = LogData::new_unchecked(vec![B256::ZERO; 5], Bytes::new());
+        assert!(matches!(
+            provider.emit_event(contract, log),
+            Err(InteropError::TooManyLogTopics { count: 5 })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_call() -> Result<()> {
+        fn answer(_input: &Bytes, _gas: u64) -> Result<(bool, Bytes, u64)> {
+            Ok((true, Bytes::from_static(&[42]), 30))
+        }
+
+        let id = Address::with_last_byte(0x77);
+        let mut provider = MockProvider::new();
+        provider.register_precompile(id, answer);
+
+        let outcome = provider.call(id, Bytes::new(), U256::ZERO, 100)?;
+        assert_eq!(
+            outcome,
+            CallOutcome {
+                success: true,
+                output: Bytes::from_static(&[42]),
+                gas_used: 30
+            }
+        );
+        assert_eq!(provider.gas_used(), 30);
+
+        let mut provider = provider.with_static(true);
+        assert!(provider.call(id, Bytes::new(), U256::ZERO, 100)?.success);
+        assert!(matches!(
+            provider.call(Address::ZERO, Bytes::new(), U256::ONE, 100),
+            Err(InteropError::StaticValueTransfer)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_create2() -> Result<()> {
+        let deployer = Address::repeat_byte(0x5e);
+        let salt = B256::repeat_byte(0x01);
+        let init_code = Bytes::from_static(&[0x60, 0x00, 0x60, 0x00, 0xf3]);
+
+        let mut provider = MockProvider::new();
+        assert!(matches!(
+            provider.create2(salt, init_code.clone(), U256::ZERO, 0),
+            Err(InteropError::MissingCaller)
+        ));
+        provider.set_caller(deployer);
+        provider.set_account(
+            deployer,
+            MockAccount {
+                balance: U256::from(10),
+                ..Default::default()
+            },
+        );
+
+        let mut ctx = RuntimeContext::new(&mut provider, deployer);
+        let (address, outcome) = ctx.create2(salt, init_code.clone(), U256::from(4), 0)?;
+        assert!(outcome.success);
+
+        let mut preimage = vec![0xff];
+        preimage.extend_from_slice(deployer.as_slice());
+        preimage.extend_from_slice(salt.as_slice());
+        preimage.extend_from_slice(keccak256(&init_code).as_slice());
+        assert_eq!(address, Address::from_slice(&keccak256(&preimage)[12..]));
+
+        // Redeploying to the same address collides
+        let (again, outcome) = ctx.create2(salt, init_code, U256::ZERO, 0)?;
+        assert_eq!(again, address);
+        assert!(!outcome.success);
+
+        assert_eq!(provider.balance(deployer)?, U256::from(6));
+        assert_eq!(provider.balance(address)?, U256::from(4));
+        assert_eq!(provider.nonce(address)?, 1);
+
+        let mut provider = provider.with_static(true);
+        assert!(matches!(
+            provider.create2(B256::ZERO, Bytes::new(), U256::ZERO, 0),
+            Err(InteropError::StaticCreate)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_destroy_account() -> Result<()> {
+        let (target, beneficiary) = (Address::repeat_byte(0xd0), Address::repeat_byte(0xbe));
+        let funded = MockAccount {
+            balance: U256::from(7),
+            nonce: 1,
+            ..Default::default()
+        };
+
+        for eip6780 in [true, false] {
+            let mut provider = MockProvider::new().with_eip6780(eip6780);
+            provider.set_account(target, funded.clone());
+            provider.sstore(target, U256::ZERO, U256::ONE)?;
+
+            provider.destroy_account(target, beneficiary)?;
+            assert_eq!(provider.balance(target)?, U256::ZERO);
+            assert_eq!(provider.balance(beneficiary)?, U256::from(7));
+
+            // Post-Cancun, accounts from earlier transactions keep their nonce and storage
+            assert_eq!(provider.account(target).is_some(), eip6780);
+            assert_eq!(provider.sload(target, U256::ZERO)?, U256::from(eip6780));
+        }
+
+        // Accounts created in the same transaction are always deleted
+        let mut provider = MockProvider::new();
+        provider.set_caller(target);
+        let (created, _) = provider.create2(B256::ZERO, Bytes::new(), U256::ZERO, 0)?;
+        provider.sstore(created, U256::ZERO, U256::ONE)?;
+        provider.destroy_account(created, created)?;
+        assert!(provider.account(created).is_none());
+        assert!(provider.storage_dump().is_empty());
+
+        let mut provider = provider.with_static(true);
+        assert!(matches!(
+            provider.destroy_account(target, beneficiary),
+            Err(InteropError::StaticSelfDestruct)
+        ));
+        Ok(())
+    }
+}
@@ -1,6 +1,55 @@
-use alloy_primitives::{Address, LogData, U256};
+use alloy_primitives::{Address, B256, Bytes, LogData, U256};
+use std::collections::HashMap;
 
-use crate::Result;
+use crate::{InteropError, Result};
+
+/// Maximum number of topics a log may carry (`LOG0` through `LOG4`).
+pub const MAX_LOG_TOPICS: usize = 4;
+
+/// An in-process precompile, called with the input and gas limit and returning
+/// `(success, output, gas_used)`.
+pub type PrecompileFn = fn(&Bytes, u64) -> Result<(bool, Bytes, u64)>;
+
+/// Precompiles reachable through [`PrecompileStorageProvider::call_precompile`].
+#[derive(Debug, Clone, Default)]
+pub struct PrecompileRegistry {
+    precompiles: HashMap<Address, PrecompileFn>,
+}
+
+impl PrecompileRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, id: Address, precompile: PrecompileFn) {
+        self.precompiles.insert(id, precompile);
+    }
+
+    pub fn contains(&self, id: &Address) -> bool {
+        self.precompiles.contains_key(id)
+    }
+
+    pub fn call(&self, id: Address, input: &Bytes, gas: u64) -> Result<(bool, Bytes, u64)> {
+        let precompile = self
+            .precompiles
+            .get(&id)
+            .ok_or(InteropError::UnknownPrecompile(id))?;
+
+        let (success, output, gas_used) = precompile(input, gas)?;
+        if gas_used > gas {
+            return Err(InteropError::OutOfGas);
+        }
+        Ok((success, output, gas_used))
+    }
+}
+
+/// Result of [`PrecompileStorageProvider::call`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallOutcome {
+    pub success: bool,
+    pub output: Bytes,
+    pub gas_used: u64,
+}
 
 pub trait PrecompileStorageProvider {
     type AccountInfo;
@@ -10,6 +59,10 @@ pub trait PrecompileStorageProvider {
     fn chain_id(&self) -> u64;
     fn timestamp(&self) -> U256;
     fn beneficiary(&self) -> Address;
+    fn block_number(&self) -> U256;
+    /// Returns the hash of block `number`, or zero if it is not one of the 256 most recent
+    /// blocks, matching the `BLOCKHASH` opcode.
+    fn block_hash(&self, number: U256) -> Result<B256>;
     fn is_static(&self) -> bool;
 
     fn sload(&self, address: Address, slot: U256) -> Result<U256>;
@@ -18,6 +71,14 @@ pub trait PrecompileStorageProvider {
     fn tload(&self, address: Address, slot: U256) -> Result<U256>;
     fn tstore(&mut self, address: Address, slot: U256, value: U256) -> Result<()>;
 
+    /// Adds `address` to the EIP-2929 accessed set without charging gas.
+    fn warm_account(&mut self, address: Address) -> Result<()>;
+    /// Adds `slot` of `address` to the EIP-2929 accessed set without charging gas.
+    fn warm_slot(&mut self, address: Address, slot: U256) -> Result<()>;
+
+    fn balance(&self, address: Address) -> Result<U256>;
+    fn nonce(&self, address: Address) -> Result<u64>;
+
     fn set_code(&mut self, address: Address, code: Self::Bytecode) -> Result<()>;
     fn with_account_info(
         &mut self,
@@ -25,13 +86,97 @@ pub trait PrecompileStorageProvider {
         f: &mut dyn FnMut(&Self::AccountInfo),
     ) -> Result<()>;
 
+    /// Emits `log` from `address`, failing with [`InteropError::TooManyLogTopics`] when it
+    /// carries more than [`MAX_LOG_TOPICS`] topics.
     fn emit_event(&mut self, address: Address, log: LogData) -> Result<()>;
 
+    /// Calls a registered in-process precompile directly, bypassing the EVM call machinery.
+    ///
+    /// Returns `(success, output, gas_used)`; the gas used is charged to this provider.
+    fn call_precompile(
+        &mut self,
+        id: Address,
+        input: Bytes,
+        gas: u64,
+    ) -> Result<(bool, Bytes, u64)>;
+
+    /// Calls `to` with `input`, transferring `value` and forwarding at most `gas`.
+    ///
+    /// The gas used is charged to this provider. Transferring value from a static context fails
+    /// with [`InteropError::StaticValueTransfer`].
+    fn call(&mut self, to: Address, input: Bytes, value: U256, gas: u64) -> Result<CallOutcome>;
+
+    /// Deploys `init_code` with CREATE2 from the provider's caller, the same account `call`
+    /// executes from. The returned address is always
+    /// `keccak256(0xff ++ caller ++ salt ++ keccak256(init_code))[12..]`; an address collision is
+    /// reported as an unsuccessful outcome rather than an error.
+    fn create2(
+        &mut self,
+        salt: B256,
+        init_code: Bytes,
+        value: U256,
+        gas: u64,
+    ) -> Result<(Address, CallOutcome)>;
+
+    /// Moves the balance of `address` to `beneficiary`, like `SELFDESTRUCT`.
+    ///
+    /// From Cancun (EIP-6780) the account is only deleted if it was created in the current
+    /// transaction; otherwise only its balance is moved. Before Cancun it is always deleted.
+    ///
+    /// Deleting an account needs a journaled self-destruct, which not every provider can record.
+    /// Such providers only support the balance-only case and fail with
+    /// [`InteropError::UnsupportedAccountDeletion`] without touching any state whenever the
+    /// account would be deleted.
+    fn destroy_account(&mut self, address: Address, beneficiary: Address) -> Result<()>;
+
     fn deduct_gas(&mut self, gas: u64) -> Result<()>;
     fn refund_gas(&mut self, gas: i64);
 
     fn gas_used(&self) -> u64;
+    fn gas_remaining(&self) -> u64;
+    /// Raw refund accumulated so far, before any end-of-transaction cap is applied.
     fn gas_refunded(&self) -> i64;
 
+    /// Checks that `amount` gas is still available without charging it, so callers can bail out
+    /// before starting an expensive operation.
+    fn try_reserve_gas(&self, amount: u64) -> Result<()> {
+        if amount > self.gas_remaining() {
+            return Err(InteropError::OutOfGas);
+        }
+        Ok(())
+    }
+
     fn spec(&self) -> Self::Spec;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockProvider;
+
+    fn echo(input: &Bytes, _gas: u64) -> Result<(bool, Bytes, u64)> {
+        Ok((true, input.clone(), 15))
+    }
+
+    #[test]
+    fn test_call_registered_precompile() -> Result<()> {
+        let id = Address::with_last_byte(0x42);
+        let mut provider = MockProvider::new();
+        provider.register_precompile(id, echo);
+
+        let input = Bytes::from_static(b"ping");
+        assert_eq!(
+            provider.call_precompile(id, input.clone(), 100)?,
+            (true, input.clone(), 15)
+        );
+        assert!(matches!(
+            provider.call_precompile(id, input.clone(), 10),
+            Err(InteropError::OutOfGas)
+        ));
+        assert!(matches!(
+            provider.call_precompile(Address::ZERO, input, 100),
+            Err(InteropError::UnknownPrecompile(addr)) if addr == Address::ZERO
+        ));
+        Ok(())
+    }
+}
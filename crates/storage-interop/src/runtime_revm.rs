@@ -1,28 +1,214 @@
-use std::cell::{Cell, RefCell};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashSet,
+};
 
-use alloy_evm::{EvmInternals, EvmInternalsError};
-use alloy_primitives::{Address, Log, LogData, U256};
+use alloy_evm::{ErasedError, EvmInternals, EvmInternalsError};
+use alloy_primitives::{Address, B256, Bytes, Log, LogData, U256};
 use revm::{
-    context::CfgEnv,
-    interpreter::gas,
+    Context, Database, Inspector, JournalEntry, MainBuilder, MainContext,
+    bytecode::opcode,
+    context::{
+        Block, BlockEnv, CfgEnv, ContextTr, JournalTr, LocalContextTr, TxEnv,
+        journal::JournalInner, result::EVMError,
+    },
+    handler::{Handler, MainnetContext, MainnetHandler},
+    inspector::InspectorHandler,
+    interpreter::{
+        CallInput, CallInputs, CallScheme, CallValue, CreateInputs, CreateScheme, FrameInput,
+        Interpreter, InterpreterResult, SharedMemory, gas,
+        interpreter_action::FrameInit,
+        interpreter_types::{InputsTr, Jumps},
+    },
+    primitives::BLOCK_HASH_HISTORY,
     primitives::hardfork::SpecId,
-    state::{AccountInfo, Bytecode},
+    state::{Account, AccountInfo, Bytecode, EvmState, EvmStorageSlot},
 };
 
 use crate::{
     InteropError,
     Result,
-    runtime_provider::PrecompileStorageProvider,
+    runtime_provider::{
+        CallOutcome, MAX_LOG_TOPICS, PrecompileFn, PrecompileRegistry, PrecompileStorageProvider,
+    },
 };
 
+/// Static cost of `SELFDESTRUCT` since Tangerine Whistle (EIP-150).
+const SELFDESTRUCT_COST: u64 = 5_000;
+
+/// Maximum size of deployed code since Spurious Dragon (EIP-170).
+const MAX_CODE_SIZE: usize = 24_576;
+
+/// Cost of an `SSTORE` writing the value the slot already holds (EIP-2200, EIP-2929).
+fn noop_sstore_cost(spec: SpecId, is_cold: bool) -> u64 {
+    if spec.is_enabled_in(SpecId::BERLIN) {
+        let cold_cost = if is_cold { gas::COLD_SLOAD_COST } else { 0 };
+        gas::WARM_STORAGE_READ_COST + cold_cost
+    } else if spec.is_enabled_in(SpecId::ISTANBUL) {
+        gas::ISTANBUL_SLOAD_GAS
+    } else {
+        gas::SSTORE_RESET
+    }
+}
+
+/// Exposes the journal to a nested EVM, so a sub-call sees the state changes made so far in the
+/// transaction.
+struct JournalDb<'r, 'a>(&'r RefCell<EvmInternals<'a>>);
+
+impl Database for JournalDb<'_, '_> {
+    type Error = ErasedError;
+
+    fn basic(&mut self, address: Address) -> std::result::Result<Option<AccountInfo>, ErasedError> {
+        let mut internals = self.0.borrow_mut();
+        let account = internals
+            .load_account_code(address)
+            .map_err(ErasedError::new)?
+            .data;
+        Ok((!account.is_loaded_as_not_existing_not_touched()).then(|| account.info.clone()))
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> std::result::Result<Bytecode, ErasedError> {
+        self.0.borrow_mut().db_mut().code_by_hash(code_hash)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> std::result::Result<U256, ErasedError> {
+        let mut internals = self.0.borrow_mut();
+        Ok(internals
+            .sload(address, index)
+            .map_err(ErasedError::new)?
+            .data)
+    }
+
+    fn block_hash(&mut self, number: u64) -> std::result::Result<B256, ErasedError> {
+        self.0.borrow_mut().db_mut().block_hash(number)
+    }
+}
+
+type FrameContext<'r, 'a> = MainnetContext<JournalDb<'r, 'a>>;
+
+/// Copies the journal's view of an account or slot into the nested EVM right before an opcode
+/// first accesses it, so the frame pays warm or cold access costs like the rest of the
+/// transaction and `SSTORE` is priced and refunded against the slot's value at the start of the
+/// transaction (EIP-2200, EIP-3529) rather than its current one.
+struct JournalSeeder<'r, 'a> {
+    internals: &'r RefCell<EvmInternals<'a>>,
+    error: Option<InteropError>,
+}
+
+impl JournalSeeder<'_, '_> {
+    fn seed_account(
+        &self,
+        journal: &mut JournalInner<JournalEntry>,
+        address: Address,
+    ) -> Result<()> {
+        if journal.state.contains_key(&address) {
+            return Ok(());
+        }
+
+        let mut internals = self.internals.borrow_mut();
+        let load = internals.load_account_code(address)?;
+        let mut account = if load.data.is_loaded_as_not_existing_not_touched() {
+            Account::new_not_existing(journal.transaction_id)
+        } else {
+            Account::from(load.data.info.clone())
+        };
+        account.transaction_id = journal.transaction_id;
+        if load.is_cold {
+            account.mark_cold();
+        }
+        journal.state.insert(address, account);
+        Ok(())
+    }
+
+    fn seed_slot(
+        &self,
+        journal: &mut JournalInner<JournalEntry>,
+        address: Address,
+        key: U256,
+    ) -> Result<()> {
+        // accounts created by the frame start with empty storage
+        let Some(account) = journal.state.get_mut(&address) else {
+            return Ok(());
+        };
+        if account.is_created() || account.storage.contains_key(&key) {
+            return Ok(());
+        }
+
+        let mut internals = self.internals.borrow_mut();
+        let created = internals.load_account(address)?.data.is_created();
+        let present = internals.sload(address, key)?;
+        let original_value = if created {
+            U256::ZERO
+        } else {
+            internals
+                .db_mut()
+                .storage(address, key)
+                .map_err(EvmInternalsError::Database)?
+        };
+        account.storage.insert(
+            key,
+            EvmStorageSlot {
+                original_value,
+                present_value: present.data,
+                transaction_id: journal.transaction_id,
+                is_cold: present.is_cold,
+            },
+        );
+        Ok(())
+    }
+}
+
+impl<'r, 'a> Inspector<FrameContext<'r, 'a>> for JournalSeeder<'r, 'a> {
+    fn step(&mut self, interp: &mut Interpreter, context: &mut FrameContext<'r, 'a>) {
+        let journal = &mut context.journaled_state.inner;
+        let stack_address = |index| {
+            interp
+                .stack
+                .peek(index)
+                .map(|word| Address::from_word(word.into()))
+        };
+        let seeded = match interp.bytecode.opcode() {
+            opcode::SLOAD | opcode::SSTORE => {
+                let address = interp.input.target_address();
+                interp
+                    .stack
+                    .peek(0)
+                    .map(|key| self.seed_slot(journal, address, key))
+            }
+            opcode::BALANCE
+            | opcode::EXTCODESIZE
+            | opcode::EXTCODECOPY
+            | opcode::EXTCODEHASH
+            | opcode::SELFDESTRUCT => {
+                stack_address(0).map(|address| self.seed_account(journal, address))
+            }
+            opcode::CALL | opcode::CALLCODE | opcode::DELEGATECALL | opcode::STATICCALL => {
+                stack_address(1).map(|address| self.seed_account(journal, address))
+            }
+            _ => Ok(Ok(())),
+        };
+
+        // a stack underflow is left for the opcode itself to report
+        if let Ok(Err(err)) = seeded {
+            self.error = Some(err);
+            interp.halt_fatal();
+        }
+    }
+}
+
 pub struct RevmStorageProvider<'a> {
     internals: RefCell<EvmInternals<'a>>,
+    caller: Option<Address>,
+    /// Accounts deployed through `create2`, which the journal only sees as regular updates.
+    created: HashSet<Address>,
     chain_id: u64,
     gas_remaining: Cell<u64>,
     gas_refunded: Cell<i64>,
     gas_limit: u64,
     spec: SpecId,
     is_static: bool,
+    skip_noop_sstore: bool,
+    precompiles: PrecompileRegistry,
 }
 
 impl<'a> RevmStorageProvider<'a> {
@@ -35,12 +221,16 @@ impl<'a> RevmStorageProvider<'a> {
     ) -> Self {
         Self {
             internals: RefCell::new(internals),
+            caller: None,
+            created: HashSet::new(),
             chain_id,
             gas_remaining: Cell::new(gas_limit),
             gas_refunded: Cell::new(0),
             gas_limit,
             spec,
             is_static,
+            skip_noop_sstore: false,
+            precompiles: PrecompileRegistry::new(),
         }
     }
 
@@ -48,6 +238,54 @@ impl<'a> RevmStorageProvider<'a> {
         Self::new(internals, u64::MAX, cfg.chain_id, cfg.spec, false)
     }
 
+    /// Registers an in-process precompile reachable through `call_precompile`.
+    pub fn register_precompile(&mut self, id: Address, precompile: PrecompileFn) {
+        self.precompiles.register(id, precompile);
+    }
+
+    /// Sets the address `call` executes from and `create2` deploys from, i.e. the precompile's
+    /// own address.
+    pub fn set_caller(&mut self, caller: Address) {
+        self.caller = Some(caller);
+    }
+
+    /// Starts metering a new logical call with `new_limit`, clearing the gas used and refunds
+    /// accumulated so far. Journaled state and warm accounts and slots are kept.
+    pub fn reset_gas(&mut self, new_limit: u64) {
+        self.gas_limit = new_limit;
+        self.gas_remaining.set(new_limit);
+        self.gas_refunded.set(0);
+    }
+
+    /// Switches the static context for subsequent calls.
+    pub fn set_static(&mut self, is_static: bool) {
+        self.is_static = is_static;
+    }
+
+    /// When enabled, `sstore` first reads the slot and, if it already holds the new value, only
+    /// charges the no-op `SSTORE` cost without writing to the journal.
+    pub fn set_skip_noop_sstore(&mut self, skip: bool) {
+        self.skip_noop_sstore = skip;
+    }
+
+    /// Returns the refund credited at the end of the transaction.
+    ///
+    /// The accumulated refund is capped at `gas_used / 5` from London onwards (EIP-3529) and at
+    /// `gas_used / 2` before.
+    pub fn final_refund(&self) -> i64 {
+        let quotient = if self.spec.is_enabled_in(SpecId::LONDON) {
+            5
+        } else {
+            2
+        };
+        let cap = (self.gas_used() / quotient).min(i64::MAX as u64) as i64;
+        self.gas_refunded.get().clamp(0, cap)
+    }
+
+    fn caller(&self) -> Result<Address> {
+        self.caller.ok_or(InteropError::MissingCaller)
+    }
+
     fn ensure_loaded_account(&self, account: Address) -> Result<()> {
         let mut internals = self.internals.borrow_mut();
         internals.load_account(account)?;
@@ -55,6 +293,115 @@ impl<'a> RevmStorageProvider<'a> {
         Ok(())
     }
 
+    /// Loads the account info, charging the EIP-2929 warm/cold account access cost.
+    fn read_account_info<R>(
+        &self,
+        address: Address,
+        f: impl FnOnce(&AccountInfo) -> R,
+    ) -> Result<R> {
+        let mut internals = self.internals.borrow_mut();
+        let account = internals.load_account(address)?.map(|a| &a.info);
+
+        self.charge_gas(gas::warm_cold_cost(account.is_cold))?;
+        Ok(f(account.data))
+    }
+
+    /// Transient storage only exists from Cancun onwards (EIP-1153).
+    fn ensure_transient_storage(&self, name: &'static str) -> Result<()> {
+        if !self.spec.is_enabled_in(SpecId::CANCUN) {
+            return Err(InteropError::UnsupportedOpcode {
+                name,
+                spec: self.spec.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Executes `frame_input` in a nested EVM on top of the journal and charges the gas it used.
+    /// If the frame succeeds, its state changes and logs are replayed into the journal and its
+    /// refund is credited.
+    ///
+    /// Accounts and slots are copied into the nested EVM as the frame first accesses them, so
+    /// access costs and `SSTORE` pricing follow the transaction's warm sets and original values.
+    /// Loading them also warms them in the journal, where a reverted frame does not cool them
+    /// down again.
+    fn execute_frame(&mut self, frame_input: FrameInput) -> Result<InterpreterResult> {
+        let block = {
+            let internals = self.internals.borrow();
+            let block_env = internals.block_env();
+            BlockEnv {
+                number: block_env.number(),
+                beneficiary: block_env.beneficiary(),
+                timestamp: block_env.timestamp(),
+                gas_limit: block_env.gas_limit(),
+                basefee: block_env.basefee(),
+                difficulty: block_env.difficulty(),
+                prevrandao: block_env.prevrandao(),
+                blob_excess_gas_and_price: block_env.blob_excess_gas_and_price(),
+            }
+        };
+        let tx = TxEnv {
+            caller: self.caller()?,
+            ..Default::default()
+        };
+
+        let seeder = JournalSeeder {
+            internals: &self.internals,
+            error: None,
+        };
+        let mut evm = Context::mainnet()
+            .with_db(JournalDb(&self.internals))
+            .with_block(block)
+            .with_tx(tx)
+            .with_cfg(CfgEnv::new_with_spec(self.spec).with_chain_id(self.chain_id))
+            .build_mainnet_with_inspector(seeder);
+        let memory = SharedMemory::new_with_buffer(evm.ctx.local().shared_memory_buffer().clone());
+        let frame = FrameInit {
+            depth: 0,
+            memory,
+            frame_input,
+        };
+
+        let mut handler = MainnetHandler::<_, EVMError<ErasedError>, _>::default();
+        let frame_result = handler.load_accounts(&mut evm).and_then(|()| {
+            // the transaction and the CALL opcode load both accounts before the frame starts
+            if let FrameInput::Call(inputs) = &frame.frame_input {
+                let journal = evm.ctx.journal_mut();
+                for address in [inputs.caller, inputs.target_address] {
+                    journal.load_account(address).map_err(EVMError::Database)?;
+                }
+            }
+            handler.inspect_run_exec_loop(&mut evm, frame)
+        });
+        if let Some(err) = evm.inspector.error.take() {
+            return Err(err);
+        }
+        let frame_result = frame_result.map_err(|err| match err {
+            EVMError::Database(err) => EvmInternalsError::Database(err).into(),
+            err => InteropError::RuntimeError(err.to_string()),
+        })?;
+        let logs = evm.ctx.journal_mut().take_logs();
+        let state = evm.ctx.journal_mut().finalize();
+        drop(evm);
+
+        let result = frame_result.into_interpreter_result();
+        if result.result.is_ok() {
+            apply_state(&mut self.internals.borrow_mut(), state, logs)?;
+        }
+
+        // a halted frame consumes all the gas it was given
+        let gas_used = if result.result.is_ok_or_revert() {
+            result.gas.spent()
+        } else {
+            result.gas.limit()
+        };
+        self.charge_gas(gas_used)?;
+        if result.result.is_ok() {
+            self.refund_gas(result.gas.refunded());
+        }
+        Ok(result)
+    }
+
     fn charge_gas(&self, gas_cost: u64) -> Result<()> {
         let remaining = self
             .gas_remaining
@@ -66,6 +413,46 @@ impl<'a> RevmStorageProvider<'a> {
     }
 }
 
+/// Replays the changes a nested EVM made to `state` into the journal.
+fn apply_state(internals: &mut EvmInternals<'_>, state: EvmState, logs: Vec<Log>) -> Result<()> {
+    // accounts created and destroyed by the frame never existed outside of it and are skipped
+    // below, but deleting any other account needs a journaled self-destruct
+    if let Some(address) = state
+        .iter()
+        .find(|(_, account)| account.is_selfdestructed() && !account.is_created())
+        .map(|(address, _)| address)
+    {
+        return Err(InteropError::UnsupportedAccountDeletion(*address));
+    }
+
+    for (address, account) in state {
+        if !account.is_touched() || account.is_selfdestructed() {
+            continue;
+        }
+
+        let current = internals.load_account(address)?.data.info.clone();
+        if account.info.balance != current.balance {
+            internals.set_balance(address, account.info.balance)?;
+        }
+        for _ in current.nonce..account.info.nonce {
+            internals.bump_nonce(address)?;
+        }
+        if account.info.code_hash != current.code_hash {
+            internals.set_code(address, account.info.code.clone().unwrap_or_default());
+        }
+        // slots carry the transaction's original values, so a slot written back to its original
+        // value looks unchanged; writing an unchanged value is a no-op in the journal
+        for (slot, value) in account.storage {
+            internals.sstore(address, slot, value.present_value)?;
+        }
+    }
+
+    for log in logs {
+        internals.log(log);
+    }
+    Ok(())
+}
+
 impl<'a> PrecompileStorageProvider for RevmStorageProvider<'a> {
     type AccountInfo = AccountInfo;
     type Bytecode = Bytecode;
@@ -83,6 +470,26 @@ impl<'a> PrecompileStorageProvider for RevmStorageProvider<'a> {
         self.internals.borrow().block_env().beneficiary()
     }
 
+    fn block_number(&self) -> U256 {
+        self.internals.borrow().block_number()
+    }
+
+    fn block_hash(&self, number: U256) -> Result<B256> {
+        self.charge_gas(gas::BLOCKHASH)?;
+
+        let current = self.block_number();
+        let in_range = number < current && current - number <= U256::from(BLOCK_HASH_HISTORY);
+        if !in_range {
+            return Ok(B256::ZERO);
+        }
+
+        let mut internals = self.internals.borrow_mut();
+        internals
+            .db_mut()
+            .block_hash(number.to::<u64>())
+            .map_err(|err| EvmInternalsError::Database(err).into())
+    }
+
     fn sload(&self, address: Address, slot: U256) -> Result<U256> {
         self.ensure_loaded_account(address)?;
         let mut internals = self.internals.borrow_mut();
@@ -96,9 +503,19 @@ impl<'a> PrecompileStorageProvider for RevmStorageProvider<'a> {
     fn sstore(&mut self, address: Address, slot: U256, value: U256) -> Result<()> {
         self.ensure_loaded_account(address)?;
         let mut internals = self.internals.borrow_mut();
+        let mut was_cold = false;
+        if self.skip_noop_sstore {
+            let current = internals.sload(address, slot)?;
+            if current.data == value {
+                return self.charge_gas(noop_sstore_cost(self.spec, current.is_cold));
+            }
+            was_cold = current.is_cold;
+        }
         let result = internals.sstore(address, slot, value)?;
+        drop(internals);
+        let is_cold = result.is_cold || was_cold;
 
-        self.charge_gas(gas::sstore_cost(self.spec, &result.data, result.is_cold))?;
+        self.charge_gas(gas::sstore_cost(self.spec, &result.data, is_cold))?;
 
         let refund = gas::sstore_refund(self.spec, &result.data);
         self.refund_gas(refund);
@@ -106,17 +523,51 @@ impl<'a> PrecompileStorageProvider for RevmStorageProvider<'a> {
     }
 
     fn tload(&self, address: Address, slot: U256) -> Result<U256> {
+        self.ensure_transient_storage("TLOAD")?;
         self.charge_gas(gas::WARM_STORAGE_READ_COST)?;
         Ok(self.internals.borrow_mut().tload(address, slot))
     }
 
     fn tstore(&mut self, address: Address, slot: U256, value: U256) -> Result<()> {
+        self.ensure_transient_storage("TSTORE")?;
         self.charge_gas(gas::WARM_STORAGE_READ_COST)?;
         self.internals.borrow_mut().tstore(address, slot, value);
         Ok(())
     }
 
+    fn warm_account(&mut self, address: Address) -> Result<()> {
+        // loading marks the account as accessed in the journal
+        self.internals.borrow_mut().load_account(address)?;
+        Ok(())
+    }
+
+    fn warm_slot(&mut self, address: Address, slot: U256) -> Result<()> {
+        self.ensure_loaded_account(address)?;
+        self.internals.borrow_mut().sload(address, slot)?;
+        Ok(())
+    }
+
+    fn balance(&self, address: Address) -> Result<U256> {
+        self.read_account_info(address, |info| info.balance)
+    }
+
+    fn nonce(&self, address: Address) -> Result<u64> {
+        self.read_account_info(address, |info| info.nonce)
+    }
+
+    /// Rejects code above the EIP-170 size limit and, from London onwards, code starting with
+    /// `0xEF` (EIP-3541). EIP-7702 delegations are exempt from the prefix check.
     fn set_code(&mut self, address: Address, code: Bytecode) -> Result<()> {
+        if self.spec.is_enabled_in(SpecId::SPURIOUS_DRAGON) && code.len() > MAX_CODE_SIZE {
+            return Err(InteropError::CodeTooLarge { size: code.len() });
+        }
+        if self.spec.is_enabled_in(SpecId::LONDON)
+            && !code.is_eip7702()
+            && code.original_byte_slice().first() == Some(&0xEF)
+        {
+            return Err(InteropError::InvalidInitCode);
+        }
+
         self.ensure_loaded_account(address)?;
         self.charge_gas(code.len() as u64 * gas::CODEDEPOSIT)?;
         self.internals.borrow_mut().set_code(address, code);
@@ -139,14 +590,175 @@ impl<'a> PrecompileStorageProvider for RevmStorageProvider<'a> {
     }
 
     fn emit_event(&mut self, address: Address, log: LogData) -> Result<()> {
-        let gas_cost = gas::log_cost(log.topics().len() as u8, log.data.len() as u64)
-            .unwrap_or(u64::MAX);
+        let count = log.topics().len();
+        if count > MAX_LOG_TOPICS {
+            return Err(InteropError::TooManyLogTopics { count });
+        }
+
+        let gas_cost = gas::log_cost(count as u8, log.data.len() as u64).unwrap_or(u64::MAX);
         self.charge_gas(gas_cost)?;
 
         self.internals.borrow_mut().log(Log { address, data: log });
         Ok(())
     }
 
+    fn call_precompile(
+        &mut self,
+        id: Address,
+        input: Bytes,
+        gas: u64,
+    ) -> Result<(bool, Bytes, u64)> {
+        let (success, output, gas_used) = self.precompiles.call(id, &input, gas)?;
+        self.charge_gas(gas_used)?;
+        Ok((success, output, gas_used))
+    }
+
+    /// Registered in-process precompiles are called directly. Any other account is called from
+    /// the [caller](Self::set_caller) in a nested EVM frame, charging the account access and
+    /// value transfer costs of `CALL` on top of the gas the frame used. Without a caller these
+    /// calls fail with [`InteropError::MissingCaller`].
+    fn call(&mut self, to: Address, input: Bytes, value: U256, gas: u64) -> Result<CallOutcome> {
+        if self.is_static && !value.is_zero() {
+            return Err(InteropError::StaticValueTransfer);
+        }
+
+        if self.precompiles.contains(&to) {
+            let (success, output, gas_used) = self.call_precompile(to, input, gas)?;
+            return Ok(CallOutcome {
+                success,
+                output,
+                gas_used,
+            });
+        }
+
+        let caller = self.caller()?;
+        let to_empty = self.read_account_info(to, |info| info.is_empty())?;
+        if !value.is_zero() {
+            let new_account_cost = if to_empty { gas::NEWACCOUNT } else { 0 };
+            self.charge_gas(gas::CALLVALUE + new_account_cost)?;
+        }
+
+        let scheme = if self.is_static {
+            CallScheme::StaticCall
+        } else {
+            CallScheme::Call
+        };
+        let inputs = CallInputs {
+            input: CallInput::Bytes(input),
+            return_memory_offset: 0..0,
+            gas_limit: gas.min(self.gas_remaining.get()),
+            bytecode_address: to,
+            known_bytecode: None,
+            target_address: to,
+            caller,
+            value: CallValue::Transfer(value),
+            scheme,
+            is_static: self.is_static,
+        };
+
+        let gas_before = self.gas_remaining.get();
+        let result = self.execute_frame(FrameInput::Call(Box::new(inputs)))?;
+        Ok(CallOutcome {
+            success: result.result.is_ok(),
+            output: result.output,
+            gas_used: gas_before - self.gas_remaining.get(),
+        })
+    }
+
+    /// Charges the `CREATE2` cost, including the EIP-3860 init code cost from Shanghai, and runs
+    /// `init_code` in a nested EVM frame, which charges the code deposit and validates the
+    /// deployed code.
+    fn create2(
+        &mut self,
+        salt: B256,
+        init_code: Bytes,
+        value: U256,
+        gas: u64,
+    ) -> Result<(Address, CallOutcome)> {
+        if self.is_static {
+            return Err(InteropError::StaticCreate);
+        }
+        let caller = self.caller()?;
+
+        let mut create_cost = gas::create2_cost(init_code.len()).unwrap_or(u64::MAX);
+        if self.spec.is_enabled_in(SpecId::SHANGHAI) {
+            if init_code.len() > 2 * MAX_CODE_SIZE {
+                return Err(InteropError::CodeTooLarge {
+                    size: init_code.len(),
+                });
+            }
+            create_cost = create_cost.saturating_add(gas::initcode_cost(init_code.len()));
+        }
+        self.charge_gas(create_cost)?;
+
+        let address = caller.create2_from_code(salt, &init_code);
+        let inputs = CreateInputs {
+            caller,
+            scheme: CreateScheme::Create2 { salt: salt.into() },
+            value,
+            init_code,
+            gas_limit: gas.min(self.gas_remaining.get()),
+        };
+
+        let gas_before = self.gas_remaining.get();
+        let result = self.execute_frame(FrameInput::Create(Box::new(inputs)))?;
+        // like `CREATE2`, only a failed deployment returns data
+        let success = result.result.is_ok();
+        if success {
+            self.created.insert(address);
+        }
+        let outcome = CallOutcome {
+            success,
+            output: if success { Bytes::new() } else { result.output },
+            gas_used: gas_before - self.gas_remaining.get(),
+        };
+        Ok((address, outcome))
+    }
+
+    /// Charges `SELFDESTRUCT` gas for the active spec, including the beneficiary access and, if
+    /// the balance would create it, the new account cost, and moves the balance with a journaled
+    /// transfer.
+    ///
+    /// `EvmInternals` cannot mark an account as self-destructed, so only the post-Cancun
+    /// balance-only case is supported. Destroying an account that would be deleted (any account
+    /// before Cancun, or one created in the current transaction since) fails with
+    /// [`InteropError::UnsupportedAccountDeletion`] before any gas is charged.
+    fn destroy_account(&mut self, address: Address, beneficiary: Address) -> Result<()> {
+        if self.is_static {
+            return Err(InteropError::StaticSelfDestruct);
+        }
+
+        let (balance, created) = {
+            let mut internals = self.internals.borrow_mut();
+            let account = internals.load_account(address)?.data;
+            let created = account.is_created() || self.created.contains(&address);
+            (account.info.balance, created)
+        };
+        if !self.spec.is_enabled_in(SpecId::CANCUN) || created {
+            return Err(InteropError::UnsupportedAccountDeletion(address));
+        }
+
+        let beneficiary_empty = self.read_account_info(beneficiary, |info| info.is_empty())?;
+        let new_account_cost = if beneficiary_empty && !balance.is_zero() {
+            gas::NEWACCOUNT
+        } else {
+            0
+        };
+        self.charge_gas(SELFDESTRUCT_COST + new_account_cost)?;
+
+        // Sending the balance to itself keeps it
+        if address == beneficiary || balance.is_zero() {
+            return Ok(());
+        }
+        let mut internals = self.internals.borrow_mut();
+        match internals.transfer(address, beneficiary, balance)? {
+            None => Ok(()),
+            Some(err) => Err(InteropError::RuntimeError(format!(
+                "balance transfer from {address} failed: {err:?}"
+            ))),
+        }
+    }
+
     fn deduct_gas(&mut self, gas: u64) -> Result<()> {
         self.charge_gas(gas)
     }
@@ -160,6 +772,10 @@ impl<'a> PrecompileStorageProvider for RevmStorageProvider<'a> {
         self.gas_limit - self.gas_remaining.get()
     }
 
+    fn gas_remaining(&self) -> u64 {
+        self.gas_remaining.get()
+    }
+
     fn gas_refunded(&self) -> i64 {
         self.gas_refunded.get()
     }
@@ -173,8 +789,717 @@ impl<'a> PrecompileStorageProvider for RevmStorageProvider<'a> {
     }
 }
 
-impl From<EvmInternalsError> for InteropError {
-    fn from(value: EvmInternalsError) -> Self {
-        Self::RuntimeError(value.to_string())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_evm::{EthEvmFactory, EvmEnv, EvmFactory};
+    use alloy_primitives::TxKind;
+    use revm::{
+        InspectEvm,
+        context::{BlockEnv, CfgEnv, JournalTr},
+        database::{CacheDB, EmptyDB},
+    };
+
+    fn with_provider<R>(
+        db: CacheDB<EmptyDB>,
+        block_env: BlockEnv,
+        spec: SpecId,
+        f: impl FnOnce(&mut RevmStorageProvider<'_>) -> R,
+    ) -> R {
+        let env = EvmEnv {
+            block_env,
+            cfg_env: CfgEnv::new_with_spec(spec),
+        };
+
+        let mut evm = EthEvmFactory::default().create_evm(db, env);
+        let ctx = evm.ctx_mut();
+        let internals = EvmInternals::new(&mut ctx.journaled_state, &ctx.block);
+        let mut provider = RevmStorageProvider::new(internals, u64::MAX, 1, spec, false);
+        f(&mut provider)
+    }
+
+    fn with_default_provider<R>(
+        db: CacheDB<EmptyDB>,
+        f: impl FnOnce(&mut RevmStorageProvider<'_>) -> R,
+    ) -> R {
+        with_provider(db, BlockEnv::default(), SpecId::default(), f)
+    }
+
+    #[test]
+    fn test_block_number_and_hash() -> Result<()> {
+        let mut db = CacheDB::new(EmptyDB::new());
+        let hash = B256::repeat_byte(0x77);
+        db.cache.block_hashes.insert(U256::from(99), hash);
+
+        let block_env = BlockEnv {
+            number: U256::from(100),
+            ..Default::default()
+        };
+        with_provider(db, block_env, SpecId::default(), |provider| {
+            assert_eq!(provider.block_number(), U256::from(100));
+            assert_eq!(provider.block_hash(U256::from(99))?, hash);
+            // the current block and anything older than 256 blocks hash to zero
+            assert_eq!(provider.block_hash(U256::from(100))?, B256::ZERO);
+            assert_eq!(provider.block_hash(U256::MAX)?, B256::ZERO);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_balance_and_nonce() -> Result<()> {
+        let funded = Address::repeat_byte(0x01);
+        let empty = Address::repeat_byte(0x02);
+
+        let mut db = CacheDB::new(EmptyDB::new());
+        db.insert_account_info(
+            funded,
+            AccountInfo {
+                balance: U256::from(1_000),
+                nonce: 3,
+                ..Default::default()
+            },
+        );
+
+        with_default_provider(db, |provider| {
+            assert_eq!(provider.balance(funded)?, U256::from(1_000));
+            assert_eq!(provider.gas_used(), gas::COLD_ACCOUNT_ACCESS_COST);
+            assert_eq!(provider.nonce(funded)?, 3);
+            assert_eq!(
+                provider.gas_used(),
+                gas::COLD_ACCOUNT_ACCESS_COST + gas::WARM_STORAGE_READ_COST
+            );
+
+            assert_eq!(provider.balance(empty)?, U256::ZERO);
+            assert_eq!(provider.nonce(empty)?, 0);
+            Ok(())
+        })
+    }
+
+    fn clear_slots(spec: SpecId, count: u64) -> Result<(u64, i64, i64)> {
+        let address = Address::repeat_byte(0x42);
+        let mut db = CacheDB::new(EmptyDB::new());
+        db.insert_account_info(address, AccountInfo::default());
+        for slot in 0..count {
+            db.insert_account_storage(address, U256::from(slot), U256::from(1))
+                .unwrap();
+        }
+
+        with_provider(db, BlockEnv::default(), spec, |provider| {
+            for slot in 0..count {
+                provider.sstore(address, U256::from(slot), U256::ZERO)?;
+            }
+            Ok((
+                provider.gas_used(),
+                provider.gas_refunded(),
+                provider.final_refund(),
+            ))
+        })
+    }
+
+    #[test]
+    fn test_final_refund_capped_post_london() -> Result<()> {
+        let (gas_used, raw, refund) = clear_slots(SpecId::LONDON, 10)?;
+
+        assert!(raw > (gas_used / 5) as i64);
+        assert_eq!(refund, (gas_used / 5) as i64);
+        Ok(())
+    }
+
+    #[test]
+    fn test_final_refund_pre_london() -> Result<()> {
+        let (gas_used, raw, refund) = clear_slots(SpecId::BERLIN, 10)?;
+
+        // the EIP-3529 cap does not apply, only the legacy half-of-gas-used cap
+        assert!(refund > (gas_used / 5) as i64);
+        assert_eq!(refund, raw.min((gas_used / 2) as i64));
+        Ok(())
+    }
+
+    #[test]
+    fn test_call_precompile() -> Result<()> {
+        fn double(input: &Bytes, _gas: u64) -> Result<(bool, Bytes, u64)> {
+            let output: Vec<u8> = input.iter().map(|b| b.wrapping_mul(2)).collect();
+            Ok((true, output.into(), 40))
+        }
+
+        let id = Address::with_last_byte(0x0f);
+        with_default_provider(CacheDB::new(EmptyDB::new()), |provider| {
+            provider.register_precompile(id, double);
+
+            let (success, output, gas_used) =
+                provider.call_precompile(id, Bytes::from_static(&[1, 2, 3]), 1_000)?;
+            assert!(success);
+            assert_eq!(output, Bytes::from_static(&[2, 4, 6]));
+            assert_eq!(gas_used, 40);
+            assert_eq!(provider.gas_used(), 40);
+
+            assert!(matches!(
+                provider.call_precompile(Address::ZERO, Bytes::new(), 1_000),
+                Err(InteropError::UnknownPrecompile(_))
+            ));
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_warmed_slot_and_account_charge_warm_cost() -> Result<()> {
+        let address = Address::repeat_byte(0x07);
+        let mut db = CacheDB::new(EmptyDB::new());
+        db.insert_account_info(address, AccountInfo::default());
+
+        with_default_provider(db, |provider| {
+            provider.warm_slot(address, U256::from(3))?;
+            provider.warm_account(Address::repeat_byte(0x08))?;
+            assert_eq!(provider.gas_used(), 0);
+
+            provider.sload(address, U256::from(3))?;
+            assert_eq!(provider.gas_used(), gas::WARM_STORAGE_READ_COST);
+
+            provider.balance(Address::repeat_byte(0x08))?;
+            assert_eq!(provider.gas_used(), 2 * gas::WARM_STORAGE_READ_COST);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_try_reserve_gas_at_limit() -> Result<()> {
+        let address = Address::repeat_byte(0x09);
+        let mut db = CacheDB::new(EmptyDB::new());
+        db.insert_account_info(address, AccountInfo::default());
+
+        with_default_provider(db, |provider| {
+            // leave exactly enough gas for one cold SLOAD
+            provider.deduct_gas(u64::MAX - gas::COLD_SLOAD_COST)?;
+            assert_eq!(provider.gas_remaining(), gas::COLD_SLOAD_COST);
+
+            assert!(matches!(
+                provider.try_reserve_gas(gas::COLD_SLOAD_COST + 1),
+                Err(InteropError::OutOfGas)
+            ));
+            provider.try_reserve_gas(gas::COLD_SLOAD_COST)?;
+            assert_eq!(provider.gas_remaining(), gas::COLD_SLOAD_COST);
+
+            provider.sload(address, U256::ZERO)?;
+            assert_eq!(provider.gas_remaining(), 0);
+            provider.try_reserve_gas(0)?;
+            assert!(matches!(
+                provider.sload(address, U256::ZERO),
+                Err(InteropError::OutOfGas)
+            ));
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_call_precompile_and_empty_account() -> Result<()> {
+        fn ok(_input: &Bytes, _gas: u64) -> Result<(bool, Bytes, u64)> {
+            Ok((true, Bytes::from_static(b"ok"), 25))
+        }
+
+        let id = Address::with_last_byte(0x0e);
+        with_default_provider(CacheDB::new(EmptyDB::new()), |provider| {
+            provider.register_precompile(id, ok);
+            let outcome = provider.call(id, Bytes::new(), U256::ZERO, 100)?;
+            assert_eq!(outcome.output, Bytes::from_static(b"ok"));
+            assert_eq!(provider.gas_used(), 25);
+
+            provider.set_caller(Address::repeat_byte(0xca));
+            let eoa = provider.call(Address::repeat_byte(0x0d), Bytes::new(), U256::ZERO, 100)?;
+            assert!(eoa.success && eoa.output.is_empty());
+            assert_eq!(eoa.gas_used, 0);
+            assert_eq!(provider.gas_used(), 25 + gas::COLD_ACCOUNT_ACCESS_COST);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_call_contract_in_nested_frame() -> Result<()> {
+        // stores the first calldata word at slot 0 and returns 42
+        let store_and_return = Bytes::from_static(&[
+            0x60, 0x00, 0x35, 0x60, 0x00, 0x55, 0x60, 0x2a, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60,
+            0x00, 0xf3,
+        ]);
+        let revert = Bytes::from_static(&[0x60, 0x00, 0x60, 0x00, 0xfd]);
+        let caller = Address::repeat_byte(0xca);
+        let (contract, reverter) = (Address::repeat_byte(0xc0), Address::repeat_byte(0xc1));
+
+        let mut db = CacheDB::new(EmptyDB::new());
+        db.insert_account_info(caller, AccountInfo::default().with_balance(U256::from(10)));
+        db.insert_account_info(
+            contract,
+            AccountInfo::default().with_code(Bytecode::new_legacy(store_and_return)),
+        );
+        db.insert_account_info(
+            reverter,
+            AccountInfo::default().with_code(Bytecode::new_legacy(revert)),
+        );
+
+        with_provider(db, BlockEnv::default(), SpecId::CANCUN, |provider| {
+            assert!(matches!(
+                provider.call(contract, Bytes::new(), U256::ZERO, 100_000),
+                Err(InteropError::MissingCaller)
+            ));
+            assert!(matches!(
+                provider.create2(B256::ZERO, Bytes::new(), U256::ZERO, 100_000),
+                Err(InteropError::MissingCaller)
+            ));
+
+            provider.set_caller(caller);
+            let input = Bytes::from(U256::from(7).to_be_bytes::<32>());
+
+            let outcome = provider.call(contract, input.clone(), U256::from(3), 100_000)?;
+            assert!(outcome.success);
+            assert_eq!(U256::from_be_slice(&outcome.output), U256::from(42));
+            // eight cheap opcodes, one word of memory and a cold zero-to-nonzero SSTORE
+            assert_eq!(
+                outcome.gas_used,
+                8 * 3 + 3 + gas::COLD_SLOAD_COST + gas::SSTORE_SET
+            );
+            assert_eq!(
+                provider.gas_used(),
+                gas::COLD_ACCOUNT_ACCESS_COST + gas::CALLVALUE + outcome.gas_used
+            );
+            assert_eq!(provider.sload(contract, U256::ZERO)?, U256::from(7));
+            assert_eq!(provider.balance(contract)?, U256::from(3));
+            assert_eq!(provider.balance(caller)?, U256::from(7));
+
+            // a reverted frame leaves the journal untouched
+            let outcome = provider.call(reverter, input.clone(), U256::from(3), 100_000)?;
+            assert!(!outcome.success);
+            assert_eq!(provider.balance(reverter)?, U256::ZERO);
+            assert_eq!(provider.balance(caller)?, U256::from(7));
+
+            // writing storage from a static context halts the frame, consuming all its gas
+            provider.set_static(true);
+            let outcome = provider.call(contract, input, U256::ZERO, 50_000)?;
+            assert!(!outcome.success);
+            assert_eq!(outcome.gas_used, 50_000);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_nested_frame_sees_warm_dirty_slot() -> Result<()> {
+        /// Records the gas of every frame executing `target`.
+        struct FrameGas {
+            target: Address,
+            frames: Vec<revm::interpreter::Gas>,
+        }
+
+        impl<CTX> Inspector<CTX> for FrameGas {
+            fn call_end(
+                &mut self,
+                _context: &mut CTX,
+                inputs: &CallInputs,
+                outcome: &mut revm::interpreter::CallOutcome,
+            ) {
+                if inputs.target_address == self.target {
+                    self.frames.push(outcome.result.gas);
+                }
+            }
+        }
+
+        let (caller, router) = (Address::repeat_byte(0xca), Address::repeat_byte(0xa0));
+        let (contract, warm) = (Address::repeat_byte(0xc0), Address::repeat_byte(0xb0));
+        // stores the first calldata word at slot 0, then reads the balance of `warm`
+        let mut store = vec![0x60, 0x00, 0x35, 0x60, 0x00, 0x55, 0x73];
+        store.extend_from_slice(warm.as_slice());
+        store.extend_from_slice(&[0x31, 0x50, 0x00]);
+        // reads the balance of `warm`, then calls `contract` with 7 and with 5
+        let mut route = vec![0x73];
+        route.extend_from_slice(warm.as_slice());
+        route.extend_from_slice(&[0x31, 0x50]);
+        for word in [0x07, 0x05] {
+            route.extend_from_slice(&[
+                0x60, word, 0x60, 0x00, 0x52, 0x60, 0x00, 0x60, 0x00, 0x60, 0x20, 0x60, 0x00, 0x60,
+                0x00, 0x73,
+            ]);
+            route.extend_from_slice(contract.as_slice());
+            route.extend_from_slice(&[0x5a, 0xf1, 0x50]);
+        }
+        route.push(0x00);
+
+        let mut db = CacheDB::new(EmptyDB::new());
+        db.insert_account_info(caller, AccountInfo::default());
+        db.insert_account_info(
+            router,
+            AccountInfo::default().with_code(Bytecode::new_legacy(route.into())),
+        );
+        db.insert_account_info(
+            contract,
+            AccountInfo::default().with_code(Bytecode::new_legacy(store.into())),
+        );
+        db.insert_account_storage(contract, U256::ZERO, U256::from(5))
+            .unwrap();
+
+        // the second call restores the original value of a slot that is warm and dirty (5 -> 7)
+        let mut evm = Context::mainnet()
+            .with_db(db.clone())
+            .with_cfg(CfgEnv::new_with_spec(SpecId::CANCUN))
+            .build_mainnet_with_inspector(FrameGas {
+                target: contract,
+                frames: Vec::new(),
+            });
+        let tx = TxEnv {
+            caller,
+            kind: TxKind::Call(router),
+            gas_limit: 1_000_000,
+            ..Default::default()
+        };
+        assert!(evm.inspect_one_tx(tx).unwrap().is_success());
+        let direct = evm.inspector.frames[1];
+        assert_eq!(direct.spent(), 4 * 3 + 2 * gas::WARM_STORAGE_READ_COST + 2);
+        assert_eq!(
+            direct.refunded(),
+            (gas::SSTORE_RESET - gas::COLD_SLOAD_COST - gas::WARM_STORAGE_READ_COST) as i64
+        );
+
+        with_provider(db, BlockEnv::default(), SpecId::CANCUN, |provider| {
+            provider.set_caller(router);
+            provider.warm_account(warm)?;
+            provider.sstore(contract, U256::ZERO, U256::from(7))?;
+            let refunded = provider.gas_refunded();
+
+            let input = Bytes::from(U256::from(5).to_be_bytes::<32>());
+            let outcome = provider.call(contract, input, U256::ZERO, 100_000)?;
+            assert!(outcome.success);
+            assert_eq!(outcome.gas_used, direct.spent());
+            assert_eq!(provider.gas_refunded() - refunded, direct.refunded());
+            assert_eq!(provider.sload(contract, U256::ZERO)?, U256::from(5));
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_create2_deploys_init_code() -> Result<()> {
+        let deployer = Address::repeat_byte(0x5e);
+        let salt = B256::repeat_byte(0x01);
+        // returns the one byte runtime code 0x2a
+        let init_code =
+            Bytes::from_static(&[0x60, 0x2a, 0x60, 0x00, 0x53, 0x60, 0x01, 0x60, 0x00, 0xf3]);
+        let expected = deployer.create2_from_code(salt, &init_code);
+
+        let mut db = CacheDB::new(EmptyDB::new());
+        db.insert_account_info(deployer, AccountInfo::default().with_balance(U256::from(5)));
+
+        with_provider(db, BlockEnv::default(), SpecId::CANCUN, |provider| {
+            provider.set_caller(deployer);
+            let (address, outcome) =
+                provider.create2(salt, init_code.clone(), U256::from(2), 100_000)?;
+            assert_eq!(address, expected);
+            assert!(outcome.success && outcome.output.is_empty());
+            // four pushes, one word of memory and the deposit of one byte of code
+            assert_eq!(outcome.gas_used, 4 * 3 + 3 + 3 + gas::CODEDEPOSIT);
+            assert_eq!(
+                provider.gas_used(),
+                gas::CREATE + gas::KECCAK256WORD + gas::INITCODE_WORD_COST + outcome.gas_used
+            );
+
+            let mut code = Bytes::new();
+            provider.with_account_info(address, &mut |info| {
+                code = info.code.as_ref().unwrap().original_bytes();
+            })?;
+            assert_eq!(code, Bytes::from_static(&[0x2a]));
+            assert_eq!(provider.nonce(address)?, 1);
+            assert_eq!(provider.balance(address)?, U256::from(2));
+            assert_eq!(provider.nonce(deployer)?, 1);
+
+            // deploying to the same address again collides
+            let (again, outcome) = provider.create2(salt, init_code, U256::ZERO, 100_000)?;
+            assert_eq!(again, expected);
+            assert!(!outcome.success);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_destroy_account_pre_and_post_cancun() -> Result<()> {
+        let (target, beneficiary) = (Address::repeat_byte(0xd0), Address::repeat_byte(0xbe));
+        let mut db = CacheDB::new(EmptyDB::new());
+        db.insert_account_info(
+            target,
+            AccountInfo::default()
+                .with_balance(U256::from(7))
+                .with_nonce(1),
+        );
+        db.insert_account_storage(target, U256::ONE, U256::from(9))
+            .unwrap();
+
+        // deleting the account needs a journaled self-destruct, so nothing changes
+        with_provider(
+            db.clone(),
+            BlockEnv::default(),
+            SpecId::SHANGHAI,
+            |provider| {
+                assert!(matches!(
+                    provider.destroy_account(target, beneficiary),
+                    Err(InteropError::UnsupportedAccountDeletion(address)) if address == target
+                ));
+                assert_eq!(provider.gas_used(), 0);
+                assert_eq!(provider.balance(target)?, U256::from(7));
+                assert_eq!(provider.nonce(target)?, 1);
+                assert_eq!(provider.sload(target, U256::ONE)?, U256::from(9));
+                assert_eq!(provider.balance(beneficiary)?, U256::ZERO);
+                Ok::<_, InteropError>(())
+            },
+        )?;
+
+        // the same holds post-Cancun for an account created in the transaction
+        with_provider(
+            db.clone(),
+            BlockEnv::default(),
+            SpecId::CANCUN,
+            |provider| {
+                provider.set_caller(beneficiary);
+                let (created, outcome) =
+                    provider.create2(B256::ZERO, Bytes::new(), U256::ZERO, 100_000)?;
+                assert!(outcome.success);
+                provider.sstore(created, U256::ONE, U256::from(9))?;
+
+                let gas_used = provider.gas_used();
+                assert!(matches!(
+                    provider.destroy_account(created, beneficiary),
+                    Err(InteropError::UnsupportedAccountDeletion(address)) if address == created
+                ));
+                assert_eq!(provider.gas_used(), gas_used);
+                assert_eq!(provider.nonce(created)?, 1);
+                assert_eq!(provider.sload(created, U256::ONE)?, U256::from(9));
+                Ok::<_, InteropError>(())
+            },
+        )?;
+
+        let env = EvmEnv {
+            cfg_env: CfgEnv::new_with_spec(SpecId::CANCUN),
+            ..Default::default()
+        };
+        let mut evm = EthEvmFactory::default().create_evm(db, env);
+        let ctx = evm.ctx_mut();
+        let checkpoint = ctx.journaled_state.checkpoint();
+        {
+            let internals = EvmInternals::new(&mut ctx.journaled_state, &ctx.block);
+            let mut provider =
+                RevmStorageProvider::new(internals, u64::MAX, 1, SpecId::CANCUN, false);
+
+            provider.destroy_account(target, beneficiary)?;
+            assert_eq!(
+                provider.gas_used(),
+                gas::COLD_ACCOUNT_ACCESS_COST + SELFDESTRUCT_COST + gas::NEWACCOUNT
+            );
+            assert_eq!(provider.gas_refunded(), 0);
+            assert_eq!(provider.balance(target)?, U256::ZERO);
+            assert_eq!(provider.balance(beneficiary)?, U256::from(7));
+        }
+
+        // the transfer is recorded in the journal and reverts with it
+        ctx.journaled_state.checkpoint_revert(checkpoint);
+        let journal = &mut ctx.journaled_state;
+        let mut balance_of = |address| journal.load_account(address).unwrap().info.balance;
+        assert_eq!(balance_of(target), U256::from(7));
+        assert_eq!(balance_of(beneficiary), U256::ZERO);
+        Ok(())
+    }
+
+    #[test]
+    fn test_emit_event_rejects_more_than_four_topics() -> Result<()> {
+        with_default_provider(CacheDB::new(EmptyDB::new()), |provider| {
+            let log = LogData::new_unchecked(vec![B256::ZERO; 5], Bytes::new());
+            assert!(matches!(
+                provider.emit_event(Address::ZERO, log),
+                Err(InteropError::TooManyLogTopics { count: 5 })
+            ));
+            assert_eq!(provider.gas_used(), 0);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_evm_internals_error_keeps_source() {
+        use std::error::Error;
+
+        let err = InteropError::from(EvmInternalsError::database(std::io::Error::other(
+            "db unavailable",
+        )));
+        assert!(matches!(
+            err,
+            InteropError::EvmInternals(EvmInternalsError::Database(_))
+        ));
+        assert_eq!(err.to_string(), "runtime error: db unavailable");
+        assert!(
+            err.source()
+                .and_then(|source| source.downcast_ref::<EvmInternalsError>())
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_reset_gas_between_calls() -> Result<()> {
+        let address = Address::repeat_byte(0x42);
+        let mut db = CacheDB::new(EmptyDB::new());
+        db.insert_account_info(address, AccountInfo::default());
+
+        with_provider(db, BlockEnv::default(), SpecId::CANCUN, |provider| {
+            provider.sstore(address, U256::ONE, U256::from(7))?;
+            // restoring a slot written in the same transaction earns a refund
+            provider.sstore(address, U256::from(2), U256::from(7))?;
+            provider.sstore(address, U256::from(2), U256::ZERO)?;
+            assert!(provider.gas_used() > 0 && provider.gas_refunded() > 0);
+
+            // the slot stays warm across logical calls
+            provider.reset_gas(10_000);
+            assert_eq!((provider.gas_used(), provider.gas_refunded()), (0, 0));
+            assert_eq!(provider.sload(address, U256::ONE)?, U256::from(7));
+            assert_eq!(provider.gas_used(), gas::WARM_STORAGE_READ_COST);
+            assert_eq!(
+                provider.gas_remaining(),
+                10_000 - gas::WARM_STORAGE_READ_COST
+            );
+
+            provider.set_static(true);
+            assert!(matches!(
+                provider.create2(B256::ZERO, Bytes::new(), U256::ZERO, 0),
+                Err(InteropError::StaticCreate)
+            ));
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_skip_noop_sstore() -> Result<()> {
+        let address = Address::repeat_byte(0x43);
+        let mut db = CacheDB::new(EmptyDB::new());
+        db.insert_account_info(address, AccountInfo::default());
+
+        with_provider(db, BlockEnv::default(), SpecId::CANCUN, |provider| {
+            provider.set_skip_noop_sstore(true);
+
+            // writing zero to a fresh slot is a cold no-op
+            provider.sstore(address, U256::from(2), U256::ZERO)?;
+            assert_eq!(
+                provider.gas_used(),
+                gas::COLD_SLOAD_COST + gas::WARM_STORAGE_READ_COST
+            );
+
+            // the first real write still pays the cold access once
+            provider.reset_gas(u64::MAX);
+            provider.sstore(address, U256::ONE, U256::from(7))?;
+            assert_eq!(provider.gas_used(), gas::COLD_SLOAD_COST + gas::SSTORE_SET);
+
+            provider.reset_gas(u64::MAX);
+            provider.sstore(address, U256::ONE, U256::from(7))?;
+            assert_eq!(provider.gas_used(), gas::WARM_STORAGE_READ_COST);
+            assert_eq!(provider.gas_refunded(), 0);
+            assert_eq!(provider.sload(address, U256::ONE)?, U256::from(7));
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_skip_noop_sstore_matches_journaled_cost() -> Result<()> {
+        let address = Address::repeat_byte(0x46);
+        let mut db = CacheDB::new(EmptyDB::new());
+        db.insert_account_info(address, AccountInfo::default());
+        db.insert_account_storage(address, U256::ONE, U256::from(5))
+            .unwrap();
+
+        for spec in [
+            SpecId::PETERSBURG,
+            SpecId::ISTANBUL,
+            SpecId::BERLIN,
+            SpecId::CANCUN,
+        ] {
+            let metered = |skip| {
+                with_provider(db.clone(), BlockEnv::default(), spec, |provider| {
+                    provider.set_skip_noop_sstore(skip);
+                    provider.sstore(address, U256::ONE, U256::from(5))?;
+                    provider.sstore(address, U256::from(2), U256::ZERO)?;
+                    provider.sstore(address, U256::from(2), U256::ZERO)?;
+                    Ok::<_, InteropError>((provider.gas_used(), provider.gas_refunded()))
+                })
+            };
+            assert_eq!(metered(true)?, metered(false)?, "{spec:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_transient_storage_requires_cancun() -> Result<()> {
+        let address = Address::repeat_byte(0x44);
+
+        with_provider(
+            CacheDB::new(EmptyDB::new()),
+            BlockEnv::default(),
+            SpecId::SHANGHAI,
+            |provider| {
+                let err = provider.tload(address, U256::ONE).unwrap_err();
+                assert!(matches!(
+                    &err,
+                    InteropError::UnsupportedOpcode { name: "TLOAD", spec } if spec == "Shanghai"
+                ));
+                assert_eq!(err.to_string(), "TLOAD is not supported by spec Shanghai");
+                assert!(matches!(
+                    provider.tstore(address, U256::ONE, U256::from(3)),
+                    Err(InteropError::UnsupportedOpcode { name: "TSTORE", .. })
+                ));
+                assert_eq!(provider.gas_used(), 0);
+            },
+        );
+
+        with_provider(
+            CacheDB::new(EmptyDB::new()),
+            BlockEnv::default(),
+            SpecId::CANCUN,
+            |provider| {
+                provider.tstore(address, U256::ONE, U256::from(3))?;
+                assert_eq!(provider.tload(address, U256::ONE)?, U256::from(3));
+                assert_eq!(provider.gas_used(), 2 * gas::WARM_STORAGE_READ_COST);
+                Ok(())
+            },
+        )
+    }
+
+    #[test]
+    fn test_set_code_validates_bytecode() -> Result<()> {
+        let address = Address::repeat_byte(0x45);
+        let oversized = Bytecode::new_legacy(Bytes::from(vec![0x00; MAX_CODE_SIZE + 1]));
+        let ef_prefixed = Bytecode::new_legacy(Bytes::from_static(&[0xef, 0x00]));
+
+        with_provider(
+            CacheDB::new(EmptyDB::new()),
+            BlockEnv::default(),
+            SpecId::CANCUN,
+            |provider| {
+                assert!(matches!(
+                    provider.set_code(address, oversized),
+                    Err(InteropError::CodeTooLarge { size }) if size == MAX_CODE_SIZE + 1
+                ));
+                assert!(matches!(
+                    provider.set_code(address, ef_prefixed.clone()),
+                    Err(InteropError::InvalidInitCode)
+                ));
+                assert_eq!(provider.gas_used(), 0);
+
+                // a 23 byte EIP-7702 delegation starts with 0xEF but is accepted
+                let delegation = Bytecode::new_eip7702(Address::repeat_byte(0x46));
+                assert_eq!(delegation.len(), 23);
+                provider.set_code(address, delegation)?;
+                assert_eq!(provider.gas_used(), 23 * gas::CODEDEPOSIT);
+
+                let max_size = Bytecode::new_legacy(Bytes::from(vec![0x00; MAX_CODE_SIZE]));
+                provider.set_code(address, max_size)?;
+                Ok::<_, InteropError>(())
+            },
+        )?;
+
+        // EIP-3541 only applies from London onwards
+        with_provider(
+            CacheDB::new(EmptyDB::new()),
+            BlockEnv::default(),
+            SpecId::BERLIN,
+            |provider| {
+                provider.set_code(address, ef_prefixed)?;
+                assert_eq!(provider.gas_used(), 2 * gas::CODEDEPOSIT);
+                Ok(())
+            },
+        )
     }
 }
@@ -1,4 +1,8 @@
 use alloy_primitives::{Address, U256};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet},
+};
 
 use crate::{
     runtime_provider::PrecompileStorageProvider,
@@ -16,6 +20,7 @@ pub struct RuntimeStorageOps<'a, P> {
     provider: &'a mut P,
     address: Address,
     mode: StorageMode,
+    touched_transient: BTreeSet<U256>,
 }
 
 impl<'a, P> RuntimeStorageOps<'a, P>
@@ -27,6 +32,7 @@ where
             provider,
             address,
             mode,
+            touched_transient: BTreeSet::new(),
         }
     }
 
@@ -37,6 +43,45 @@ where
     pub fn mode(&self) -> StorageMode {
         self.mode
     }
+
+    /// Starts buffering writes: repeated stores to a slot are coalesced and only the last value
+    /// reaches the provider, in slot order, when the batch is committed.
+    ///
+    /// Loads through the batch see its pending writes. The batch must be ended with
+    /// [`StorageBatch::commit_batch`] or [`StorageBatch::discard`]; see [`Self::with_batch`] for
+    /// a version that picks one based on the result.
+    pub fn begin_batch(&mut self) -> StorageBatch<'_, 'a, P> {
+        StorageBatch {
+            ops: self,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// Runs `f` against a new batch, committing it if `f` succeeds and discarding it otherwise.
+    pub fn with_batch<R>(
+        &mut self,
+        f: impl FnOnce(&mut StorageBatch<'_, 'a, P>) -> Result<R>,
+    ) -> Result<R> {
+        let mut batch = self.begin_batch();
+        match f(&mut batch) {
+            Ok(value) => batch.commit_batch().map(|()| value),
+            Err(err) => {
+                batch.discard();
+                Err(err)
+            }
+        }
+    }
+
+    /// Resets every transient slot written through these ops back to zero.
+    ///
+    /// EIP-1153 already discards transient storage at the end of the transaction, so this is
+    /// only needed to reuse transient slots as scratch space within a single call.
+    pub fn clear_transient(&mut self) -> Result<()> {
+        for slot in std::mem::take(&mut self.touched_transient) {
+            self.provider.tstore(self.address, slot, U256::ZERO)?;
+        }
+        Ok(())
+    }
 }
 
 impl<'a, P> StorageOps for RuntimeStorageOps<'a, P>
@@ -53,7 +98,296 @@ where
     fn store(&mut self, slot: U256, value: U256) -> Result<()> {
         match self.mode {
             StorageMode::Persistent => self.provider.sstore(self.address, slot, value),
-            StorageMode::Transient => self.provider.tstore(self.address, slot, value),
+            StorageMode::Transient => {
+                self.touched_transient.insert(slot);
+                self.provider.tstore(self.address, slot, value)
+            }
+        }
+    }
+}
+
+/// Coalescing write buffer over a [`RuntimeStorageOps`], see [`RuntimeStorageOps::begin_batch`].
+///
+/// Nothing is written until [`commit_batch`](Self::commit_batch). Dropping a batch that still
+/// holds pending writes discards them, and trips a debug assertion since it usually means a
+/// missing commit; call [`discard`](Self::discard) to drop them on purpose.
+#[must_use = "pending writes are discarded unless the batch is committed"]
+pub struct StorageBatch<'o, 'a, P>
+where
+    P: PrecompileStorageProvider,
+{
+    ops: &'o mut RuntimeStorageOps<'a, P>,
+    pending: BTreeMap<U256, U256>,
+}
+
+impl<P> StorageBatch<'_, '_, P>
+where
+    P: PrecompileStorageProvider,
+{
+    /// Number of distinct slots waiting to be written.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Writes the final value of every buffered slot to the provider.
+    pub fn commit_batch(mut self) -> Result<()> {
+        for (slot, value) in std::mem::take(&mut self.pending) {
+            self.ops.store(slot, value)?;
+        }
+        Ok(())
+    }
+
+    /// Drops every pending write without touching storage.
+    pub fn discard(mut self) {
+        self.pending.clear();
+    }
+}
+
+impl<P> Drop for StorageBatch<'_, '_, P>
+where
+    P: PrecompileStorageProvider,
+{
+    fn drop(&mut self) {
+        debug_assert!(
+            self.pending.is_empty() || std::thread::panicking(),
+            "storage batch dropped with {} uncommitted writes",
+            self.pending.len()
+        );
+    }
+}
+
+impl<P> StorageOps for StorageBatch<'_, '_, P>
+where
+    P: PrecompileStorageProvider,
+{
+    fn load(&self, slot: U256) -> Result<U256> {
+        match self.pending.get(&slot) {
+            Some(value) => Ok(*value),
+            None => self.ops.load(slot),
+        }
+    }
+
+    fn store(&mut self, slot: U256, value: U256) -> Result<()> {
+        self.pending.insert(slot, value);
+        Ok(())
+    }
+}
+
+/// Persistent and transient storage of one address, usable side by side.
+///
+/// Both views share the provider through a `RefCell` that is only borrowed for the duration of
+/// each individual load or store, so they can be interleaved freely.
+pub struct DualStorageOps<'a, P> {
+    provider: RefCell<&'a mut P>,
+    address: Address,
+}
+
+impl<'a, P> DualStorageOps<'a, P>
+where
+    P: PrecompileStorageProvider,
+{
+    pub fn new(provider: &'a mut P, address: Address) -> Self {
+        Self {
+            provider: RefCell::new(provider),
+            address,
+        }
+    }
+
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    pub fn persistent(&self) -> StorageView<'_, 'a, P> {
+        StorageView {
+            ops: self,
+            mode: StorageMode::Persistent,
+        }
+    }
+
+    pub fn transient(&self) -> StorageView<'_, 'a, P> {
+        StorageView {
+            ops: self,
+            mode: StorageMode::Transient,
+        }
+    }
+}
+
+/// One storage mode of a [`DualStorageOps`].
+pub struct StorageView<'d, 'a, P> {
+    ops: &'d DualStorageOps<'a, P>,
+    mode: StorageMode,
+}
+
+impl<P> StorageView<'_, '_, P> {
+    pub fn mode(&self) -> StorageMode {
+        self.mode
+    }
+}
+
+impl<P> StorageOps for StorageView<'_, '_, P>
+where
+    P: PrecompileStorageProvider,
+{
+    fn load(&self, slot: U256) -> Result<U256> {
+        let provider = self.ops.provider.borrow();
+        match self.mode {
+            StorageMode::Persistent => provider.sload(self.ops.address, slot),
+            StorageMode::Transient => provider.tload(self.ops.address, slot),
+        }
+    }
+
+    fn store(&mut self, slot: U256, value: U256) -> Result<()> {
+        let mut provider = self.ops.provider.borrow_mut();
+        match self.mode {
+            StorageMode::Persistent => provider.sstore(self.ops.address, slot, value),
+            StorageMode::Transient => provider.tstore(self.ops.address, slot, value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        BytesLikeHandler, Handler, InteropError, MockProvider, Storable, VecHandler,
+        runtime_context::RuntimeContext,
+    };
+
+    #[test]
+    fn test_clear_transient() -> Result<()> {
+        let mut provider = MockProvider::new();
+        let address = Address::repeat_byte(0x01);
+        let mut ops = RuntimeStorageOps::new(&mut provider, address, StorageMode::Transient);
+
+        for slot in 0..4u64 {
+            ops.store(U256::from(slot), U256::from(slot + 100))?;
+        }
+        assert_eq!(ops.load(U256::from(2))?, U256::from(102));
+
+        ops.clear_transient()?;
+        for slot in 0..4u64 {
+            assert_eq!(ops.load(U256::from(slot))?, U256::ZERO);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_dual_ops_read_transient_write_persistent() -> Result<()> {
+        let mut provider = MockProvider::new();
+        let address = Address::repeat_byte(0x02);
+        provider.tstore(address, U256::ONE, U256::from(7))?;
+
+        let mut ctx = RuntimeContext::new(&mut provider, address);
+        ctx.with_both(|persistent, transient| {
+            let pending = transient.load(U256::ONE)?;
+            persistent.store(U256::ONE, pending * U256::from(2))?;
+            transient.store(U256::ONE, U256::ZERO)
+        })?;
+
+        assert_eq!(provider.sload(address, U256::ONE)?, U256::from(14));
+        assert_eq!(provider.tload(address, U256::ONE)?, U256::ZERO);
+        Ok(())
+    }
+
+    #[test]
+    fn test_delete_dynamic_types_in_transient_mode() -> Result<()> {
+        let mut provider = MockProvider::new();
+        let address = Address::repeat_byte(0x03);
+        let (vec_slot, string_slot) = (U256::from(1), U256::from(2));
+        let values: Vec<u64> = (1..=9).collect();
+        let text = "transient ".repeat(5);
+
+        // identical persistent values must survive the transient deletes
+        let mut persistent =
+            RuntimeStorageOps::new(&mut provider, address, StorageMode::Persistent);
+        VecHandler::<u64>::new(vec_slot).write(&mut persistent, values.clone())?;
+        BytesLikeHandler::<String>::new(string_slot).write(&mut persistent, text.clone())?;
+
+        let mut transient = RuntimeStorageOps::new(&mut provider, address, StorageMode::Transient);
+        let mut vec = VecHandler::<u64>::new(vec_slot);
+        let mut string = BytesLikeHandler::<String>::new(string_slot);
+        vec.write(&mut transient, values.clone())?;
+        string.write(&mut transient, text.clone())?;
+
+        let mut slots = Vec::<u64>::raw_slots(&transient, vec_slot)?;
+        slots.extend(String::raw_slots(&transient, string_slot)?);
+        // length slot plus three data slots, and header slot plus two data slots
+        assert_eq!(slots.len(), 7);
+
+        vec.delete(&mut transient)?;
+        string.delete(&mut transient)?;
+        for slot in &slots {
+            assert_eq!(transient.load(*slot)?, U256::ZERO);
         }
+        assert!(vec.read(&transient)?.is_empty());
+        assert!(string.read(&transient)?.is_empty());
+
+        let persistent = RuntimeStorageOps::new(&mut provider, address, StorageMode::Persistent);
+        assert_eq!(VecHandler::<u64>::new(vec_slot).read(&persistent)?, values);
+        assert_eq!(
+            BytesLikeHandler::<String>::new(string_slot).read(&persistent)?,
+            text
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_batch_coalesces_writes_per_slot() -> Result<()> {
+        let mut provider = MockProvider::new();
+        let address = Address::repeat_byte(0x04);
+        let mut ops = RuntimeStorageOps::new(&mut provider, address, StorageMode::Persistent);
+        let mut vec = VecHandler::<U256>::new(U256::from(6));
+
+        let mut batch = ops.begin_batch();
+        vec.write(&mut batch, (1..=3u64).map(U256::from).collect())?;
+        assert_eq!(vec.len(&batch)?, 3);
+        vec.delete(&mut batch)?;
+        // the length slot and three data slots
+        assert_eq!(batch.pending(), 4);
+        batch.commit_batch()?;
+
+        assert!(vec.read(&ops)?.is_empty());
+        assert_eq!(provider.sstore_count(), 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_failed_batch_discards_writes() -> Result<()> {
+        let mut provider = MockProvider::new();
+        let address = Address::repeat_byte(0x05);
+        let mut ops = RuntimeStorageOps::new(&mut provider, address, StorageMode::Persistent);
+        let mut vec = VecHandler::<U256>::new(U256::from(6));
+        vec.push(&mut ops, U256::from(1))?;
+
+        // the second write fails after the push was buffered
+        let result = ops.with_batch(|batch| {
+            vec.push(batch, U256::from(2))?;
+            vec.set(batch, 5, U256::from(3))
+        });
+        assert!(matches!(result, Err(InteropError::IndexOutOfBounds { .. })));
+        assert_eq!(vec.read(&ops)?, [U256::from(1)]);
+
+        ops.with_batch(|batch| vec.push(batch, U256::from(2)))?;
+        assert_eq!(vec.read(&ops)?, [U256::from(1), U256::from(2)]);
+        assert_eq!(provider.sstore_count(), 4);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg_attr(
+        debug_assertions,
+        should_panic(expected = "dropped with 1 uncommitted writes")
+    )]
+    fn test_dropped_batch_asserts_in_debug() {
+        let mut provider = MockProvider::new();
+        let address = Address::repeat_byte(0x06);
+        let mut ops = RuntimeStorageOps::new(&mut provider, address, StorageMode::Persistent);
+
+        let mut batch = ops.begin_batch();
+        batch.store(U256::ONE, U256::from(7)).unwrap();
+        drop(batch);
+
+        assert_eq!(ops.load(U256::ONE).unwrap(), U256::ZERO);
     }
 }
@@ -1,9 +1,10 @@
 use alloy_primitives::U256;
 
 use crate::{
-    packing::FieldLocation,
+    packing::{self, FieldLocation},
     layout::{Handler, LayoutCtx, Storable, StorableType},
     storage::StorageOps,
+    InteropError,
     Result,
 };
 
@@ -24,8 +25,25 @@ impl<T> Slot<T> {
         }
     }
 
+    /// Equivalent to [`Slot::new`]; every handler has an `at_slot` constructor.
     #[inline]
-    pub fn new_with_ctx(slot: U256, ctx: LayoutCtx) -> Self {
+    pub fn at_slot(slot: U256) -> Self {
+        Self::new(slot)
+    }
+
+    /// Points at `slot` with the given layout context.
+    ///
+    /// Only packable types may be given a packed context. That is checked by a debug assertion
+    /// here since `ctx` is a runtime value; [`Slot::new_at_loc`] rejects it at compile time.
+    #[inline]
+    pub fn new_with_ctx(slot: U256, ctx: LayoutCtx) -> Self
+    where
+        T: StorableType,
+    {
+        debug_assert!(
+            T::IS_PACKABLE || ctx == LayoutCtx::FULL,
+            "only packable types can be given a packed context"
+        );
         Self {
             slot,
             ctx,
@@ -42,20 +60,60 @@ impl<T> Slot<T> {
         }
     }
 
+    /// Points at a packed field, failing with [`InteropError::PackedSlotOverflow`] if the field
+    /// would cross the slot boundary.
+    ///
+    /// Only packable types can live at a byte offset, so using a dynamic or multi-slot type is
+    /// rejected when the call is compiled:
+    ///
+    /// ```compile_fail
+    /// use alloy_primitives::U256;
+    /// use tempo_storage_interop::{FieldLocation, Slot};
+    ///
+    /// let _ = Slot::<Vec<u8>>::new_at_loc(U256::ZERO, FieldLocation::new(0, 4, 32));
+    /// ```
     #[inline]
-    pub fn new_at_loc(base_slot: U256, loc: FieldLocation) -> Self
+    pub fn new_at_loc(base_slot: U256, loc: FieldLocation) -> Result<Self>
     where
         T: StorableType,
     {
-        debug_assert!(
-            T::IS_PACKABLE,
-            "Slot::new_at_loc can only be used with packable types"
-        );
-        Self {
+        const {
+            assert!(
+                T::IS_PACKABLE,
+                "Slot::new_at_loc can only be used with packable types"
+            )
+        };
+        if loc.offset_bytes + T::BYTES > 32 {
+            return Err(InteropError::PackedSlotOverflow {
+                slot: Some(base_slot + U256::from(loc.offset_slots)),
+                offset: loc.offset_bytes,
+                bytes: T::BYTES,
+            });
+        }
+
+        Ok(Self {
             slot: base_slot + U256::from(loc.offset_slots),
             ctx: LayoutCtx::packed(loc.offset_bytes),
             _ty: std::marker::PhantomData,
-        }
+        })
+    }
+
+    /// Points at element `index` of a fixed-size array starting at `base_slot`, using the same
+    /// element location rules as `ArrayHandler::at` but without needing the array length.
+    ///
+    /// No bounds check is performed.
+    #[inline]
+    pub fn at_index(base_slot: U256, index: usize) -> Self
+    where
+        T: StorableType,
+    {
+        let (slot, offset) = packing::fixed_array_element_slot(base_slot, index, T::BYTES);
+        let ctx = if T::BYTES <= 16 {
+            LayoutCtx::packed(offset)
+        } else {
+            LayoutCtx::FULL
+        };
+        Self::new_with_ctx(slot, ctx)
     }
 
     #[inline]
@@ -67,18 +125,240 @@ impl<T> Slot<T> {
     pub const fn offset(&self) -> Option<usize> {
         self.ctx.packed_offset()
     }
+
+    /// Applies `f` to the raw word backing this slot and stores the result.
+    ///
+    /// Always operates on the full 32-byte word, regardless of the packed offset.
+    pub fn modify_raw<S, F>(&mut self, storage: &mut S, f: F) -> Result<()>
+    where
+        S: StorageOps,
+        F: FnOnce(U256) -> Result<U256>,
+    {
+        let word = storage.load(self.slot)?;
+        storage.store(self.slot, f(word)?)
+    }
+}
+
+impl<T: Storable> Slot<T> {
+    /// Returns the raw words of every slot backing this value, without decoding it.
+    ///
+    /// Dynamic types also include their data region. Packed values return their shared slot.
+    pub fn read_raw<S: StorageOps>(&self, storage: &S) -> Result<Vec<U256>> {
+        let slots = match self.ctx.packed_offset() {
+            Some(_) => vec![self.slot],
+            None => T::raw_slots(storage, self.slot)?,
+        };
+        slots.into_iter().map(|slot| storage.load(slot)).collect()
+    }
+}
+
+impl<T> Default for Slot<T> {
+    fn default() -> Self {
+        Self::new(U256::ZERO)
+    }
 }
 
 impl<T: Storable> Handler<T> for Slot<T> {
     fn read<S: StorageOps>(&self, storage: &S) -> Result<T> {
-        T::load(storage, self.slot, self.ctx)
+        T::load(storage, self.slot, self.ctx).map_err(|err| err.with_slot(self.slot))
     }
 
     fn write<S: StorageOps>(&mut self, storage: &mut S, value: T) -> Result<()> {
-        value.store(storage, self.slot, self.ctx)
+        value
+            .store(storage, self.slot, self.ctx)
+            .map_err(|err| err.with_slot(self.slot))
     }
 
     fn delete<S: StorageOps>(&mut self, storage: &mut S) -> Result<()> {
-        T::delete(storage, self.slot, self.ctx)
+        T::delete(storage, self.slot, self.ctx).map_err(|err| err.with_slot(self.slot))
+    }
+}
+
+/// Shorthand for [`Slot::at_index`].
+#[inline]
+pub fn array_element<T: StorableType>(base_slot: U256, index: usize) -> Slot<T> {
+    Slot::at_index(base_slot, index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Mapping,
+        array::ArrayHandler,
+        bytes_like::BytesLikeHandler,
+        storage::StorageKey,
+        test_util::{CountingStorage, MemoryStorage},
+        vec::VecHandler,
+    };
+    use alloy_primitives::Address;
+
+    #[test]
+    fn test_modify_raw_single_load_and_store() -> Result<()> {
+        let mut storage = CountingStorage::default();
+        let mut slot = Slot::<U256>::new(U256::from(7));
+        slot.write(&mut storage, U256::from(0xff00))?;
+        storage.reset();
+
+        slot.modify_raw(&mut storage, |word| Ok(word | U256::from(0x00ff)))?;
+
+        assert_eq!(storage.loads(), 1);
+        assert_eq!(storage.stores(), 1);
+        assert_eq!(slot.read(&storage)?, U256::from(0xffff));
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_raw_long_string() -> Result<()> {
+        let mut storage = CountingStorage::default();
+        let value = "a".repeat(40);
+        let mut slot = Slot::<String>::new(U256::from(2));
+        slot.write(&mut storage, value.clone())?;
+
+        let raw = slot.read_raw(&storage)?;
+        assert_eq!(raw.len(), 3);
+        assert_eq!(raw[0], U256::from(value.len() * 2 + 1));
+
+        let mut data = raw[1].to_be_bytes::<32>().to_vec();
+        data.extend_from_slice(&raw[2].to_be_bytes::<32>()[..8]);
+        assert_eq!(data, value.as_bytes());
+        assert!(raw[2].to_be_bytes::<32>()[8..].iter().all(|b| *b == 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_array_element_matches_array_handler() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+        let base = U256::from(5);
+
+        let mut packed = ArrayHandler::<u16, 40>::new(base);
+        let values: [u16; 40] = std::array::from_fn(|i| i as u16 * 3);
+        packed.write(&mut storage, values)?;
+        for index in [0, 1, 15, 16, 39] {
+            let element = array_element::<u16>(base, index);
+            let expected = packed.at(index).unwrap();
+            assert_eq!(
+                (element.slot(), element.offset()),
+                (expected.slot(), expected.offset())
+            );
+            assert_eq!(element.read(&storage)?, values[index]);
+        }
+
+        let base = U256::from(100);
+        let mut unpacked = ArrayHandler::<U256, 3>::new(base);
+        let values = [U256::from(1), U256::MAX, U256::from(3)];
+        unpacked.write(&mut storage, values)?;
+        for (index, value) in values.into_iter().enumerate() {
+            let element = Slot::<U256>::at_index(base, index);
+            assert_eq!(element.slot(), unpacked.at(index).unwrap().slot());
+            assert_eq!(element.offset(), None);
+            assert_eq!(element.read(&storage)?, value);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_new_at_loc_rejects_slot_spanning_field() -> Result<()> {
+        let slot = Slot::<u64>::new_at_loc(U256::ONE, FieldLocation::new(1, 24, 8))?;
+        assert_eq!((slot.slot(), slot.offset()), (U256::from(2), Some(24)));
+
+        assert!(matches!(
+            Slot::<u64>::new_at_loc(U256::ONE, FieldLocation::new(0, 28, 8)),
+            Err(InteropError::PackedSlotOverflow {
+                slot: Some(slot),
+                offset: 28,
+                bytes: 8
+            }) if slot == U256::ONE
+        ));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg_attr(
+        debug_assertions,
+        should_panic(expected = "only packable types can be given a packed context")
+    )]
+    fn test_packed_ctx_rejected_for_multi_slot_type() {
+        let _ = Slot::<(U256, u8)>::new_with_ctx(U256::ONE, LayoutCtx::packed(4));
+    }
+
+    #[test]
+    #[cfg_attr(debug_assertions, should_panic(expected = "String cannot be packed"))]
+    fn test_packed_ctx_rejected_for_dynamic_handler() {
+        let _ = String::handle(U256::ONE, LayoutCtx::packed(4));
+    }
+
+    #[test]
+    fn test_layout_struct_of_handlers() -> Result<()> {
+        struct Token {
+            owner: Slot<Address>,
+            balances: Mapping<Address, U256>,
+            holders: VecHandler<Address>,
+            name: BytesLikeHandler<String>,
+            limits: ArrayHandler<u64, 4>,
+        }
+
+        let mut token = Token {
+            owner: Slot::default(),
+            balances: Mapping::at_slot(U256::from(1)),
+            holders: VecHandler::at_slot(U256::from(2)),
+            name: BytesLikeHandler::at_slot(U256::from(3)),
+            limits: ArrayHandler::at_slot(U256::from(4)),
+        };
+        let mut storage = MemoryStorage::new();
+        let alice = Address::repeat_byte(0xa1);
+
+        token.owner.write(&mut storage, alice)?;
+        token
+            .balances
+            .at(alice)
+            .write(&mut storage, U256::from(100))?;
+        token.holders.push(&mut storage, alice)?;
+        token.name.write(&mut storage, "Token".into())?;
+        token.limits.write(&mut storage, [1, 2, 3, 4])?;
+
+        assert_eq!(
+            storage.load(U256::ZERO)?,
+            U256::from_be_slice(alice.as_slice())
+        );
+        assert_eq!(
+            storage.load(alice.mapping_slot(U256::from(1)))?,
+            U256::from(100)
+        );
+        assert_eq!(storage.load(U256::from(2))?, U256::ONE);
+        assert_eq!(token.name.read(&storage)?, "Token");
+        assert_eq!(
+            storage.load(U256::from(4))?,
+            (U256::from(4) << 192) | (U256::from(3) << 128) | (U256::from(2) << 64) | U256::ONE
+        );
+
+        assert_eq!(VecHandler::<u8>::default().len_slot(), U256::ZERO);
+        Ok(())
+    }
+
+    #[test]
+    fn test_packed_overflow_reports_slot() {
+        let mut storage = MemoryStorage::new();
+        let mut field = Slot::<u64>::new_with_ctx(U256::from(0x2a), LayoutCtx::packed(28));
+
+        let err = field.read(&storage).unwrap_err();
+        assert!(matches!(
+            err,
+            InteropError::PackedSlotOverflow {
+                slot: Some(slot),
+                offset: 28,
+                bytes: 8
+            } if slot == U256::from(0x2a)
+        ));
+        assert_eq!(
+            err.to_string(),
+            "packed value spans slot boundary: slot=0x2a, offset=28, bytes=8"
+        );
+
+        // stores report the overflow as an error too, rather than tripping an assertion
+        assert_eq!(
+            field.write(&mut storage, 7).unwrap_err().to_string(),
+            "packed value spans slot boundary: slot=0x2a, offset=28, bytes=8"
+        );
     }
 }
@@ -1,16 +1,198 @@
-use alloy_primitives::{Address, B256, U256, keccak256};
+use alloy_primitives::{Address, B256, FixedBytes, I256, U256, keccak256};
 
 use crate::Result;
 
 pub trait StorageOps {
     fn load(&self, slot: U256) -> Result<U256>;
     fn store(&mut self, slot: U256, value: U256) -> Result<()>;
+
+    /// Hints that `slots` are about to be loaded, so latency-bound backends can batch-fetch them.
+    /// In-memory backends keep the default no-op.
+    fn prefetch(&self, slots: &[U256]) -> Result<()> {
+        let _ = slots;
+        Ok(())
+    }
+
+    /// Returns whether `slot` holds a non-zero word.
+    fn exists(&self, slot: U256) -> Result<bool> {
+        Ok(!self.load(slot)?.is_zero())
+    }
+
+    /// Loads the words at `slot` and `slot + 1`, e.g. the high and low halves of a `U512`.
+    fn load_u256_pair(&self, slot: U256) -> Result<(U256, U256)> {
+        Ok((self.load(slot)?, self.load(slot + U256::ONE)?))
+    }
+
+    /// Loads the `n_slots` consecutive words starting at `slot`, in slot order.
+    fn load_wide(&self, slot: U256, n_slots: usize) -> Result<Vec<U256>> {
+        let slots: Vec<_> = (0..n_slots).map(|i| slot + U256::from(i)).collect();
+        self.prefetch(&slots)?;
+        slots.into_iter().map(|slot| self.load(slot)).collect()
+    }
+
+    /// Loads `slot` converted to `T`, or `default` if the slot is zero.
+    ///
+    /// Bounded on `Self: Sized` to keep the trait usable as `dyn StorageOps`.
+    fn load_or<T: From<U256>>(&self, slot: U256, default: T) -> Result<T>
+    where
+        Self: Sized,
+    {
+        let value = self.load(slot)?;
+        Ok(if value.is_zero() {
+            default
+        } else {
+            T::from(value)
+        })
+    }
+
+    /// Loads `slot` and zeroes it, returning the previous value. The store is skipped when the
+    /// slot is already zero.
+    fn take(&mut self, slot: U256) -> Result<U256> {
+        let value = self.load(slot)?;
+        if !value.is_zero() {
+            self.store(slot, U256::ZERO)?;
+        }
+        Ok(value)
+    }
+}
+
+/// Forwards to the borrowed backend, so handlers can be driven through `&mut dyn StorageOps`.
+impl<S: StorageOps + ?Sized> StorageOps for &mut S {
+    fn load(&self, slot: U256) -> Result<U256> {
+        (**self).load(slot)
+    }
+
+    fn store(&mut self, slot: U256, value: U256) -> Result<()> {
+        (**self).store(slot, value)
+    }
+
+    fn prefetch(&self, slots: &[U256]) -> Result<()> {
+        (**self).prefetch(slots)
+    }
+
+    fn exists(&self, slot: U256) -> Result<bool> {
+        (**self).exists(slot)
+    }
+
+    fn load_u256_pair(&self, slot: U256) -> Result<(U256, U256)> {
+        (**self).load_u256_pair(slot)
+    }
+
+    fn load_wide(&self, slot: U256, n_slots: usize) -> Result<Vec<U256>> {
+        (**self).load_wide(slot, n_slots)
+    }
+
+    fn take(&mut self, slot: U256) -> Result<U256> {
+        (**self).take(slot)
+    }
+}
+
+impl<S: StorageOps + ?Sized> StorageOps for Box<S> {
+    fn load(&self, slot: U256) -> Result<U256> {
+        (**self).load(slot)
+    }
+
+    fn store(&mut self, slot: U256, value: U256) -> Result<()> {
+        (**self).store(slot, value)
+    }
+
+    fn prefetch(&self, slots: &[U256]) -> Result<()> {
+        (**self).prefetch(slots)
+    }
+
+    fn exists(&self, slot: U256) -> Result<bool> {
+        (**self).exists(slot)
+    }
+
+    fn load_u256_pair(&self, slot: U256) -> Result<(U256, U256)> {
+        (**self).load_u256_pair(slot)
+    }
+
+    fn load_wide(&self, slot: U256, n_slots: usize) -> Result<Vec<U256>> {
+        (**self).load_wide(slot, n_slots)
+    }
+
+    fn take(&mut self, slot: U256) -> Result<U256> {
+        (**self).take(slot)
+    }
+}
+
+/// Prefetches the `count` consecutive slots starting at `start`, or only the first
+/// [`PREALLOCATION_LIMIT`](crate::packing::PREALLOCATION_LIMIT) of them for longer ranges.
+pub(crate) fn prefetch_range<S: StorageOps>(storage: &S, start: U256, count: usize) -> Result<()> {
+    if count == 0 {
+        return Ok(());
+    }
+    let count = count.min(crate::packing::PREALLOCATION_LIMIT);
+    let slots: Vec<_> = (0..count).map(|i| start + U256::from(i)).collect();
+    storage.prefetch(&slots)
+}
+
+/// Storage adapter that drops writes which cannot change already-zero target slots.
+///
+/// A store is skipped when the value is zero, or when it equals the word just loaded from the
+/// same slot (the read-modify-write of a zero element into a packed slot).
+pub(crate) struct SkipZeroWrites<'a, S> {
+    inner: &'a mut S,
+    last_load: std::cell::Cell<Option<(U256, U256)>>,
+}
+
+impl<'a, S: StorageOps> SkipZeroWrites<'a, S> {
+    pub(crate) fn new(inner: &'a mut S) -> Self {
+        Self {
+            inner,
+            last_load: std::cell::Cell::new(None),
+        }
+    }
+}
+
+impl<S: StorageOps> StorageOps for SkipZeroWrites<'_, S> {
+    fn load(&self, slot: U256) -> Result<U256> {
+        let value = self.inner.load(slot)?;
+        self.last_load.set(Some((slot, value)));
+        Ok(value)
+    }
+
+    fn store(&mut self, slot: U256, value: U256) -> Result<()> {
+        if value.is_zero() || self.last_load.take() == Some((slot, value)) {
+            return Ok(());
+        }
+        self.inner.store(slot, value)
+    }
+
+    fn prefetch(&self, slots: &[U256]) -> Result<()> {
+        self.inner.prefetch(slots)
+    }
+}
+
+/// Hash function used to derive mapping and dynamic data-region slots.
+///
+/// Solidity layouts always use keccak256; other implementations are meant for accelerated
+/// keccak backends or for fuzzing with a cheap mock hash.
+pub trait Keccak {
+    fn hash(input: &[u8]) -> B256;
+}
+
+/// The default [`Keccak`] backend, `alloy_primitives::keccak256`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Keccak256;
+
+impl Keccak for Keccak256 {
+    #[inline]
+    fn hash(input: &[u8]) -> B256 {
+        keccak256(input)
+    }
 }
 
 pub trait StorageKey {
     fn as_storage_bytes(&self) -> impl AsRef<[u8]>;
 
     fn mapping_slot(&self, slot: U256) -> U256 {
+        self.mapping_slot_with::<Keccak256>(slot)
+    }
+
+    /// Like [`mapping_slot`](Self::mapping_slot), hashing the preimage with `H`.
+    fn mapping_slot_with<H: Keccak>(&self, slot: U256) -> U256 {
         let key_bytes = self.as_storage_bytes();
         let key_bytes = key_bytes.as_ref();
         let padded_len = key_bytes.len().div_ceil(32) * 32;
@@ -19,7 +201,7 @@ pub trait StorageKey {
         buf[padded_len - key_bytes.len()..padded_len].copy_from_slice(key_bytes);
         buf[padded_len..].copy_from_slice(&slot.to_be_bytes::<32>());
 
-        U256::from_be_bytes(keccak256(&buf).0)
+        U256::from_be_bytes(H::hash(&buf).0)
     }
 }
 
@@ -29,9 +211,21 @@ impl StorageKey for Address {
     }
 }
 
-impl StorageKey for B256 {
+/// `bytesN` keys are left-aligned and right-padded to 32 bytes, as Solidity encodes them.
+impl<const N: usize> StorageKey for FixedBytes<N> {
     fn as_storage_bytes(&self) -> impl AsRef<[u8]> {
-        self.as_slice()
+        self.0.as_storage_bytes()
+    }
+}
+
+/// Treated like `bytesN`: left-aligned and right-padded to 32 bytes.
+impl<const N: usize> StorageKey for [u8; N] {
+    fn as_storage_bytes(&self) -> impl AsRef<[u8]> {
+        const { assert!(N <= 32, "fixed-size byte keys are at most 32 bytes") };
+
+        let mut word = [0u8; 32];
+        word[..N].copy_from_slice(self);
+        word
     }
 }
 
@@ -40,3 +234,246 @@ impl StorageKey for U256 {
         self.to_be_bytes::<32>()
     }
 }
+
+impl StorageKey for I256 {
+    fn as_storage_bytes(&self) -> impl AsRef<[u8]> {
+        self.to_be_bytes::<32>()
+    }
+}
+
+/// Signed keys are sign-extended to 32 bytes, matching Solidity's `intN` key encoding.
+macro_rules! impl_signed_storage_key {
+    ($($ty:ty),+) => {$(
+        impl StorageKey for $ty {
+            fn as_storage_bytes(&self) -> impl AsRef<[u8]> {
+                let bytes = self.to_be_bytes();
+                let mut word = if *self < 0 { [0xff; 32] } else { [0; 32] };
+                word[32 - bytes.len()..].copy_from_slice(&bytes);
+                word
+            }
+        }
+    )+};
+}
+
+impl_signed_storage_key!(i8, i16, i32, i64, i128);
+
+/// Composite keys hash both parts at once: the slot is `keccak256(abi.encode(a, b, slot))`,
+/// with each part padded to whole words the same way it is as a standalone key.
+///
+/// Solidity has no tuple keys, so this is a Rust-side convention rather than a layout `solc`
+/// produces. It differs from nesting `mapping(A => mapping(B => V))`, and `(a, b)` and `(b, a)`
+/// resolve to different slots.
+impl<A: StorageKey, B: StorageKey> StorageKey for (A, B) {
+    fn as_storage_bytes(&self) -> impl AsRef<[u8]> {
+        let mut buf = Vec::with_capacity(64);
+        for part in [
+            self.0.as_storage_bytes().as_ref(),
+            self.1.as_storage_bytes().as_ref(),
+        ] {
+            let padded_len = part.len().div_ceil(32) * 32;
+            buf.resize(buf.len() + padded_len - part.len(), 0);
+            buf.extend_from_slice(part);
+        }
+        buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Handler, Slot, data_region_slot_with, test_util::MemoryStorage};
+    use alloy_primitives::U512;
+    use std::cell::Cell;
+
+    fn solidity_mapping_slot(key: [u8; 32], slot: u64) -> U256 {
+        let mut preimage = [0u8; 64];
+        preimage[..32].copy_from_slice(&key);
+        preimage[56..].copy_from_slice(&slot.to_be_bytes());
+        U256::from_be_bytes(keccak256(preimage).0)
+    }
+
+    #[test]
+    fn test_int256_keys_are_twos_complement() {
+        // mapping(int256 => uint256) at slot 1
+        assert_eq!(
+            I256::MINUS_ONE.mapping_slot(U256::ONE),
+            solidity_mapping_slot([0xff; 32], 1)
+        );
+
+        let key = I256::try_from(-256).unwrap();
+        let mut word = [0xff; 32];
+        word[31] = 0x00;
+        assert_eq!(key.mapping_slot(U256::ONE), solidity_mapping_slot(word, 1));
+
+        let key = I256::try_from(5).unwrap();
+        assert_eq!(
+            key.mapping_slot(U256::ONE),
+            U256::from(5).mapping_slot(U256::ONE)
+        );
+    }
+
+    #[test]
+    fn test_signed_primitive_keys_are_sign_extended() {
+        // mapping(int64 => uint256) at slot 1
+        assert_eq!(
+            (-1i64).mapping_slot(U256::ONE),
+            solidity_mapping_slot([0xff; 32], 1)
+        );
+        assert_eq!(
+            (-1i8).mapping_slot(U256::ONE),
+            I256::MINUS_ONE.mapping_slot(U256::ONE)
+        );
+        assert_eq!(
+            i128::MIN.mapping_slot(U256::ONE),
+            I256::try_from(i128::MIN).unwrap().mapping_slot(U256::ONE)
+        );
+        assert_eq!(
+            7i32.mapping_slot(U256::ONE),
+            U256::from(7).mapping_slot(U256::ONE)
+        );
+    }
+
+    #[test]
+    fn test_fixed_bytes_keys_are_right_padded() {
+        // mapping(bytes32 => uint256) at slot 2
+        let key = B256::repeat_byte(0xab);
+        assert_eq!(
+            key.mapping_slot(U256::from(2)),
+            solidity_mapping_slot([0xab; 32], 2)
+        );
+
+        // mapping(bytes4 => uint256) at slot 2
+        let mut word = [0u8; 32];
+        word[..4].copy_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+        let expected = solidity_mapping_slot(word, 2);
+        assert_eq!(
+            FixedBytes::<4>::new([0xde, 0xad, 0xbe, 0xef]).mapping_slot(U256::from(2)),
+            expected
+        );
+        assert_eq!(
+            [0xde, 0xad, 0xbe, 0xef].mapping_slot(U256::from(2)),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_exists_load_or_and_take() -> Result<()> {
+        let mut storage = MemoryStorage::new();
+        let counter = U256::from(4);
+
+        assert!(!storage.exists(counter)?);
+        assert_eq!(storage.load_or(counter, U256::from(10))?, U256::from(10));
+
+        storage.store(counter, U256::from(3))?;
+        assert!(storage.exists(counter)?);
+        assert_eq!(storage.load_or(counter, U256::from(10))?, U256::from(3));
+
+        assert_eq!(storage.take(counter)?, U256::from(3));
+        assert!(!storage.exists(counter)?);
+        assert_eq!(storage.take(counter)?, U256::ZERO);
+        Ok(())
+    }
+
+    #[test]
+    fn test_handlers_through_trait_objects() -> Result<()> {
+        let mut backend = MemoryStorage::new();
+        let mut slot = Slot::<(u64, Address)>::new(U256::from(3));
+        let value = (7, Address::repeat_byte(0x42));
+
+        let mut storage: &mut dyn StorageOps = &mut backend;
+        slot.write(&mut storage, value)?;
+        assert_eq!(slot.read(&storage)?, value);
+        assert_eq!(slot.read(&backend)?, value);
+
+        let mut boxed: Box<dyn StorageOps> = Box::new(backend);
+        Slot::<U256>::new(U256::ONE).write(&mut boxed, U256::from(5))?;
+        assert_eq!(boxed.load_or(U256::ONE, U256::ZERO)?, U256::from(5));
+        assert_eq!(boxed.take(U256::ONE)?, U256::from(5));
+        assert!(!boxed.exists(U256::ONE)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_tuple_keys_hash_both_parts_in_order() {
+        let (id, holder) = (U256::from(9), Address::repeat_byte(0x77));
+        let key = (id, holder);
+
+        let mut preimage = [0u8; 96];
+        preimage[..32].copy_from_slice(&id.to_be_bytes::<32>());
+        preimage[44..64].copy_from_slice(holder.as_slice());
+        preimage[95] = 3;
+        let expected = U256::from_be_bytes(keccak256(preimage).0);
+
+        assert_eq!(key.mapping_slot(U256::from(3)), expected);
+        assert_eq!((id, holder).mapping_slot(U256::from(3)), expected);
+
+        let (a, b) = (U256::from(1), U256::from(2));
+        assert_ne!(
+            (a, b).mapping_slot(U256::ONE),
+            (b, a).mapping_slot(U256::ONE)
+        );
+        assert_ne!(
+            (a, b).mapping_slot(U256::ONE),
+            b.mapping_slot(a.mapping_slot(U256::ONE))
+        );
+    }
+
+    thread_local! {
+        static MOCK_HASHES: Cell<usize> = const { Cell::new(0) };
+    }
+
+    /// Counts invocations and returns the first word of the preimage unhashed.
+    struct MockKeccak;
+
+    impl Keccak for MockKeccak {
+        fn hash(input: &[u8]) -> B256 {
+            MOCK_HASHES.set(MOCK_HASHES.get() + 1);
+            B256::from_slice(&input[..32])
+        }
+    }
+
+    #[test]
+    fn test_custom_keccak_backend() {
+        let key = Address::repeat_byte(0x42);
+        assert_eq!(
+            key.mapping_slot_with::<MockKeccak>(U256::from(7)),
+            U256::from_be_slice(key.as_slice())
+        );
+        assert_eq!(
+            data_region_slot_with::<MockKeccak>(U256::from(7)),
+            U256::from(7)
+        );
+        assert_eq!(MOCK_HASHES.get(), 2);
+
+        // the default path is still keccak256
+        assert_eq!(
+            key.mapping_slot(U256::from(7)),
+            key.mapping_slot_with::<Keccak256>(U256::from(7))
+        );
+        assert_eq!(MOCK_HASHES.get(), 2);
+    }
+
+    #[test]
+    fn test_load_wide_keeps_slot_order() -> Result<()> {
+        let mut storage = MemoryStorage::new();
+        let value = U512::from_be_bytes(std::array::from_fn::<u8, 64, _>(|i| i as u8));
+        Slot::<U512>::new(U256::from(8)).write(&mut storage, value)?;
+        storage.store(U256::from(10), U256::from(0xaa))?;
+        storage.store(U256::from(11), U256::MAX)?;
+
+        let words = storage.load_wide(U256::from(8), 4)?;
+        assert_eq!(words.len(), 4);
+        // the high word of a U512 comes first
+        assert_eq!(words[0].to_be_bytes::<32>()[0], 0);
+        assert_eq!(words[1].to_be_bytes::<32>()[31], 63);
+        assert_eq!(words[2..], [U256::from(0xaa), U256::MAX]);
+        assert_eq!(storage.load_u256_pair(U256::from(8))?, (words[0], words[1]));
+
+        let bytes: Vec<u8> = words[..2]
+            .iter()
+            .flat_map(|word| word.to_be_bytes::<32>())
+            .collect();
+        assert_eq!(bytes, value.to_be_bytes::<64>());
+        Ok(())
+    }
+}
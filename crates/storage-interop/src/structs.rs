@@ -0,0 +1,394 @@
+//! Named structs laid out like Solidity structs.
+//!
+//! [`storable_struct!`](crate::storable_struct) declares a struct, implements [`Storable`] for
+//! it with the same packing rules as tuples and records where each field lives, so tooling can
+//! resolve a field by name without knowing the Rust type.
+//!
+//! [`Storable`]: crate::Storable
+
+use alloy_primitives::U256;
+
+use crate::{
+    layout::{Layout, LayoutCtx, StorableType},
+    packing::{FieldLocation, calc_field_locs},
+};
+
+/// Name and resolved location of a struct field, relative to the struct's base slot.
+#[derive(Debug, Clone, Copy)]
+pub struct FieldInfo {
+    pub name: &'static str,
+    pub loc: FieldLocation,
+    pub layout: Layout,
+}
+
+impl FieldInfo {
+    /// Returns the absolute slot and layout context of this field for a struct at `base_slot`.
+    #[inline]
+    pub fn resolve(&self, base_slot: U256) -> (U256, LayoutCtx) {
+        let slot = base_slot + U256::from(self.loc.offset_slots);
+        if self.layout.is_packable() {
+            (slot, LayoutCtx::packed(self.loc.offset_bytes))
+        } else {
+            (slot, LayoutCtx::FULL)
+        }
+    }
+}
+
+/// Field metadata of a struct declared with [`storable_struct!`](crate::storable_struct).
+pub trait StructLayout: StorableType {
+    /// Fields in declaration order.
+    const FIELDS: &'static [FieldInfo];
+
+    /// Resolves the slot and layout context of `field_name` for a struct stored at `base_slot`.
+    fn field_slot(base_slot: U256, field_name: &str) -> Option<(U256, LayoutCtx)> {
+        Self::FIELDS
+            .iter()
+            .find(|field| field.name == field_name)
+            .map(|field| field.resolve(base_slot))
+    }
+}
+
+#[doc(hidden)]
+pub const fn field_infos<const N: usize>(
+    names: [&'static str; N],
+    layouts: [Layout; N],
+) -> [FieldInfo; N] {
+    let locs = calc_field_locs(layouts);
+    let mut fields = [FieldInfo {
+        name: "",
+        loc: FieldLocation::new(0, 0, 0),
+        layout: Layout::Bytes(0),
+    }; N];
+
+    let mut i = 0;
+    while i < N {
+        fields[i] = FieldInfo {
+            name: names[i],
+            loc: locs[i],
+            layout: layouts[i],
+        };
+        i += 1;
+    }
+    fields
+}
+
+/// Declares a struct stored with Solidity's struct layout and implements [`StructLayout`] for
+/// it.
+///
+/// Fields may be any `Storable` type and follow the same packing rules as tuples. Starting the
+/// declaration with `#[describe]` also implements [`DescribeLayout`](crate::DescribeLayout),
+/// which requires every field type to implement it too.
+///
+/// ```
+/// use alloy_primitives::{Address, U256};
+/// use tempo_storage_interop::{DescribeLayout, StructLayout, storable_struct};
+///
+/// storable_struct! {
+///     #[describe]
+///     pub struct Config {
+///         pub admin: Address,
+///         pub paused: bool,
+///         pub fee: U256,
+///     }
+/// }
+///
+/// let (slot, ctx) = Config::field_slot(U256::from(5), "paused").unwrap();
+/// assert_eq!((slot, ctx.packed_offset()), (U256::from(5), Some(20)));
+/// assert_eq!(Config::storage_layout().type_name, "struct Config");
+/// ```
+#[macro_export]
+macro_rules! storable_struct {
+    (
+        #[describe]
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($(#[$field_meta:meta])* $field_vis:vis $field:ident: $ty:ty),+ $(,)?
+        }
+    ) => {
+        $crate::storable_struct! {
+            $(#[$meta])*
+            $vis struct $name {
+                $($(#[$field_meta])* $field_vis $field: $ty,)+
+            }
+        }
+
+        impl $crate::DescribeLayout for $name {
+            fn storage_layout() -> $crate::StorageLayout {
+                let members = $crate::__private::describe_fields(
+                    [$(stringify!($field)),+],
+                    [$((
+                        <$ty as $crate::StorableType>::LAYOUT,
+                        <$ty as $crate::DescribeLayout>::storage_layout(),
+                    )),+],
+                );
+                $crate::StorageLayout::structure(
+                    concat!("struct ", stringify!($name)),
+                    <Self as $crate::StorableType>::SLOTS,
+                    members,
+                )
+            }
+        }
+    };
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($(#[$field_meta:meta])* $field_vis:vis $field:ident: $ty:ty),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $($(#[$field_meta])* $field_vis $field: $ty,)+
+        }
+
+        impl $crate::StructLayout for $name {
+            const FIELDS: &'static [$crate::FieldInfo] = &$crate::__private::field_infos(
+                [$(stringify!($field)),+],
+                [$(<$ty as $crate::StorableType>::LAYOUT),+],
+            );
+        }
+
+        impl $crate::StorableType for $name {
+            const LAYOUT: $crate::Layout = $crate::Layout::Slots(
+                $crate::__private::calc_struct_slots([$(<$ty as $crate::StorableType>::LAYOUT),+]),
+            );
+            type Handler = $crate::Slot<Self>;
+
+            fn handle(slot: $crate::__private::U256, ctx: $crate::LayoutCtx) -> Self::Handler {
+                $crate::Slot::new_with_ctx(slot, ctx)
+            }
+        }
+
+        impl $crate::Storable for $name {
+            fn load<S: $crate::StorageOps>(
+                storage: &S,
+                slot: $crate::__private::U256,
+                ctx: $crate::LayoutCtx,
+            ) -> $crate::Result<Self> {
+                debug_assert_eq!(ctx, $crate::LayoutCtx::FULL, "Structs cannot be packed");
+                let [$($field),+] = const {
+                    $crate::__private::calc_field_locs([$(<$ty as $crate::StorableType>::LAYOUT),+])
+                };
+
+                let mut reader = $crate::__private::FieldReader::new(storage, slot);
+                Ok(Self {
+                    $($field: reader.field::<$ty>($field)?,)+
+                })
+            }
+
+            fn store<S: $crate::StorageOps>(
+                &self,
+                storage: &mut S,
+                slot: $crate::__private::U256,
+                ctx: $crate::LayoutCtx,
+            ) -> $crate::Result<()> {
+                debug_assert_eq!(ctx, $crate::LayoutCtx::FULL, "Structs cannot be packed");
+                let [$($field),+] = const {
+                    $crate::__private::calc_field_locs([$(<$ty as $crate::StorableType>::LAYOUT),+])
+                };
+
+                let mut writer = $crate::__private::FieldWriter::new(storage, slot);
+                $(writer.field(&self.$field, $field)?;)+
+                writer.finish()
+            }
+
+            fn delete<S: $crate::StorageOps>(
+                storage: &mut S,
+                slot: $crate::__private::U256,
+                ctx: $crate::LayoutCtx,
+            ) -> $crate::Result<()> {
+                debug_assert_eq!(ctx, $crate::LayoutCtx::FULL, "Structs cannot be packed");
+                let [$($field),+] = const {
+                    $crate::__private::calc_field_locs([$(<$ty as $crate::StorableType>::LAYOUT),+])
+                };
+
+                let mut writer = $crate::__private::FieldWriter::new(storage, slot);
+                $(writer.clear::<$ty>($field)?;)+
+                writer.finish()
+            }
+
+            fn raw_slots<S: $crate::StorageOps>(
+                storage: &S,
+                slot: $crate::__private::U256,
+            ) -> $crate::Result<Vec<$crate::__private::U256>> {
+                let [$($field),+] = const {
+                    $crate::__private::calc_field_locs([$(<$ty as $crate::StorableType>::LAYOUT),+])
+                };
+
+                let reader = $crate::__private::FieldReader::new(storage, slot);
+                let mut slots = Vec::with_capacity(<Self as $crate::StorableType>::SLOTS);
+                $(reader.raw_slots::<$ty>($field, &mut slots)?;)+
+                Ok(slots)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::{Address, U256};
+
+    use super::StructLayout;
+    use crate::{
+        ArrayHandler, Handler, Mapping, Result, Slot, StorableType, StorageKey, StorageOps,
+        VecHandler, test_util::MemoryStorage,
+    };
+
+    storable_struct! {
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        struct Config {
+            admin: Address,
+            paused: bool,
+            fee: U256,
+            name: String,
+        }
+    }
+
+    storable_struct! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        struct PolicyData {
+            policy_type: u8,
+            admin: Address,
+            limit: U256,
+        }
+    }
+
+    // `PolicyData` doesn't implement `DescribeLayout`, which is fine without `#[describe]`
+    storable_struct! {
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        struct PolicyRecord {
+            data: PolicyData,
+            members: Vec<Address>,
+        }
+    }
+
+    fn policy(seed: u8) -> PolicyData {
+        PolicyData {
+            policy_type: seed,
+            admin: Address::repeat_byte(seed),
+            limit: U256::from(seed) << 200,
+        }
+    }
+
+    #[test]
+    fn test_struct_in_mapping() -> Result<()> {
+        let mut storage = MemoryStorage::new();
+        let policies = Mapping::<U256, PolicyData>::new(U256::ONE);
+        assert_eq!(PolicyData::SLOTS, 2);
+
+        policies.at(U256::from(7)).write(&mut storage, policy(1))?;
+        policies.at(U256::from(8)).write(&mut storage, policy(2))?;
+        assert_eq!(policies.at(U256::from(7)).read(&storage)?, policy(1));
+        assert_eq!(policies.at(U256::from(8)).read(&storage)?, policy(2));
+
+        // `uint8 policyType; address admin;` share the mapping slot, `limit` follows it
+        let base = U256::from(7).mapping_slot(U256::ONE);
+        assert_eq!(
+            storage.load(base)?,
+            U256::from(1) | (U256::from_be_slice(&[1; 20]) << 8)
+        );
+        assert_eq!(storage.load(base + U256::ONE)?, policy(1).limit);
+
+        // mapping declared as the second slot of an enclosing struct
+        let nested = Mapping::<U256, PolicyData>::at_offset(U256::from(10), 1, U256::from(3));
+        assert_eq!(nested.slot(), U256::from(3).mapping_slot(U256::from(11)));
+
+        policies.at(U256::from(7)).delete(&mut storage)?;
+        assert_eq!(storage.dump_nonzero().len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_struct_in_arrays() -> Result<()> {
+        let mut storage = MemoryStorage::new();
+        let base = U256::from(4);
+
+        let mut array = ArrayHandler::<PolicyData, 3>::new(base);
+        array.write(&mut storage, [policy(1), policy(2), policy(3)])?;
+        let element = array.at(2).unwrap();
+        assert_eq!(element.slot(), base + U256::from(4));
+        assert_eq!(element.read(&storage)?, policy(3));
+
+        let mut vec = VecHandler::<PolicyData>::new(U256::from(20));
+        vec.write(&mut storage, vec![policy(4), policy(5)])?;
+        vec.push(&mut storage, policy(6))?;
+        let element = vec.at(&storage, 1)?.unwrap();
+        assert_eq!(element.slot(), vec.data_slot() + U256::from(2));
+        assert_eq!(vec.read(&storage)?, vec![policy(4), policy(5), policy(6)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_struct_round_trip() -> Result<()> {
+        let mut storage = MemoryStorage::new();
+        let mut slot = Slot::<Config>::new(U256::from(2));
+        let config = Config {
+            admin: Address::repeat_byte(0x11),
+            paused: true,
+            fee: U256::from(30),
+            name: "tempo".to_string(),
+        };
+
+        slot.write(&mut storage, config.clone())?;
+        assert_eq!(slot.read(&storage)?, config);
+        // admin and paused share a slot, and the short name lives inline
+        assert_eq!(slot.read_raw(&storage)?.len(), 3);
+
+        slot.delete(&mut storage)?;
+        assert!(storage.dump_nonzero().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_nested_struct_round_trip() -> Result<()> {
+        let mut storage = MemoryStorage::new();
+        let mut slot = Slot::<PolicyRecord>::new(U256::from(4));
+        let record = PolicyRecord {
+            data: policy(3),
+            members: vec![Address::repeat_byte(0x31), Address::repeat_byte(0x32)],
+        };
+
+        slot.write(&mut storage, record.clone())?;
+        assert_eq!(slot.read(&storage)?, record);
+        assert_eq!(PolicyRecord::SLOTS, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_field_slot_by_name() -> Result<()> {
+        let base = U256::from(2);
+        let mut storage = MemoryStorage::new();
+        Slot::<Config>::new(base).write(
+            &mut storage,
+            Config {
+                admin: Address::repeat_byte(0x22),
+                paused: true,
+                fee: U256::from(7),
+                name: String::new(),
+            },
+        )?;
+
+        let (slot, ctx) = Config::field_slot(base, "admin").unwrap();
+        assert_eq!((slot, ctx.packed_offset()), (base, Some(0)));
+        assert_eq!(
+            Slot::<Address>::new_with_ctx(slot, ctx).read(&storage)?,
+            Address::repeat_byte(0x22)
+        );
+
+        let (slot, ctx) = Config::field_slot(base, "paused").unwrap();
+        assert_eq!((slot, ctx.packed_offset()), (base, Some(20)));
+        assert!(Slot::<bool>::new_with_ctx(slot, ctx).read(&storage)?);
+
+        let (slot, ctx) = Config::field_slot(base, "fee").unwrap();
+        assert_eq!(slot, base + U256::ONE);
+        assert_eq!(
+            Slot::<U256>::new_with_ctx(slot, ctx).read(&storage)?,
+            U256::from(7)
+        );
+
+        let (slot, ctx) = Config::field_slot(base, "name").unwrap();
+        assert_eq!((slot, ctx), (base + U256::from(2), crate::LayoutCtx::FULL));
+
+        assert!(Config::field_slot(base, "owner").is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+//! Shared helpers for unit tests.
+
+use std::cell::Cell;
+
+use alloy_primitives::U256;
+
+use crate::{Result, storage::StorageOps};
+
+pub(crate) use crate::memory::MemoryStorage;
+
+/// Memory storage that counts backend accesses.
+#[derive(Debug, Default)]
+pub(crate) struct CountingStorage {
+    inner: MemoryStorage,
+    loads: Cell<usize>,
+    stores: usize,
+}
+
+impl CountingStorage {
+    pub(crate) fn loads(&self) -> usize {
+        self.loads.get()
+    }
+
+    pub(crate) fn stores(&self) -> usize {
+        self.stores
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.loads.set(0);
+        self.stores = 0;
+    }
+}
+
+impl StorageOps for CountingStorage {
+    fn load(&self, slot: U256) -> Result<U256> {
+        self.loads.set(self.loads.get() + 1);
+        self.inner.load(slot)
+    }
+
+    fn store(&mut self, slot: U256, value: U256) -> Result<()> {
+        self.stores += 1;
+        self.inner.store(slot, value)
+    }
+}
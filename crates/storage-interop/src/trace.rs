@@ -0,0 +1,161 @@
+//! [`StorageOps`] adapter recording every access, for debugging and access-list generation.
+
+use alloy_primitives::U256;
+use std::{cell::RefCell, collections::BTreeSet};
+
+use crate::{Result, storage::StorageOps};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageAccess {
+    Load { slot: U256, value: U256 },
+    Store { slot: U256, value: U256 },
+}
+
+impl StorageAccess {
+    #[inline]
+    pub const fn slot(&self) -> U256 {
+        match self {
+            Self::Load { slot, .. } | Self::Store { slot, .. } => *slot,
+        }
+    }
+}
+
+/// Wraps a [`StorageOps`] backend and records every load and store in order.
+#[derive(Debug, Default)]
+pub struct TracingStorage<S> {
+    inner: S,
+    trace: RefCell<Vec<StorageAccess>>,
+}
+
+impl<S> TracingStorage<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            trace: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    /// Returns all recorded accesses in order.
+    pub fn trace(&self) -> Vec<StorageAccess> {
+        self.trace.borrow().clone()
+    }
+
+    /// Returns the `(slot, value)` pairs of every load, in order.
+    pub fn reads(&self) -> Vec<(U256, U256)> {
+        self.trace
+            .borrow()
+            .iter()
+            .filter_map(|access| match access {
+                StorageAccess::Load { slot, value } => Some((*slot, *value)),
+                StorageAccess::Store { .. } => None,
+            })
+            .collect()
+    }
+
+    /// Returns the `(slot, value)` pairs of every store, in order.
+    pub fn writes(&self) -> Vec<(U256, U256)> {
+        self.trace
+            .borrow()
+            .iter()
+            .filter_map(|access| match access {
+                StorageAccess::Store { slot, value } => Some((*slot, *value)),
+                StorageAccess::Load { .. } => None,
+            })
+            .collect()
+    }
+
+    /// Returns every touched slot, deduplicated and sorted, as needed for an EIP-2930 access
+    /// list entry.
+    pub fn access_list(&self) -> Vec<U256> {
+        self.trace
+            .borrow()
+            .iter()
+            .map(StorageAccess::slot)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.trace.get_mut().clear();
+    }
+}
+
+impl<S: StorageOps> StorageOps for TracingStorage<S> {
+    fn load(&self, slot: U256) -> Result<U256> {
+        let value = self.inner.load(slot)?;
+        self.trace
+            .borrow_mut()
+            .push(StorageAccess::Load { slot, value });
+        Ok(value)
+    }
+
+    fn store(&mut self, slot: U256, value: U256) -> Result<()> {
+        self.inner.store(slot, value)?;
+        self.trace
+            .get_mut()
+            .push(StorageAccess::Store { slot, value });
+        Ok(())
+    }
+
+    fn prefetch(&self, slots: &[U256]) -> Result<()> {
+        self.inner.prefetch(slots)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::Address;
+
+    use super::*;
+    use crate::{Handler, Mapping, storage::StorageKey, test_util::MemoryStorage};
+
+    #[test]
+    fn test_trace_mapping_write() -> Result<()> {
+        let mut storage = TracingStorage::new(MemoryStorage::new());
+        let balances = Mapping::<Address, (u64, u64)>::new(U256::from(4));
+        let (alice, bob) = (Address::repeat_byte(0xa1), Address::repeat_byte(0xb0));
+
+        balances.at(alice).write(&mut storage, (1, 2))?;
+        balances.at(bob).write(&mut storage, (3, 4))?;
+        assert_eq!(balances.at(alice).read(&storage)?, (1, 2));
+
+        let (alice_slot, bob_slot) = (
+            alice.mapping_slot(U256::from(4)),
+            bob.mapping_slot(U256::from(4)),
+        );
+        let alice_word = U256::from(1) | (U256::from(2) << 64);
+        assert_eq!(
+            storage.trace(),
+            vec![
+                StorageAccess::Store {
+                    slot: alice_slot,
+                    value: alice_word
+                },
+                StorageAccess::Store {
+                    slot: bob_slot,
+                    value: U256::from(3) | (U256::from(4) << 64)
+                },
+                StorageAccess::Load {
+                    slot: alice_slot,
+                    value: alice_word
+                },
+            ]
+        );
+        assert_eq!(storage.reads(), vec![(alice_slot, alice_word)]);
+        assert_eq!(storage.writes().len(), 2);
+
+        let mut expected = vec![alice_slot, bob_slot];
+        expected.sort();
+        assert_eq!(storage.access_list(), expected);
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+//! Newtypes stored exactly like the primitive they wrap.
+
+/// Implements `StorableType`, `Packable` and `DescribeLayout` for a single-field tuple struct by
+/// forwarding to its packable inner type, so distinct newtypes keep the inner type's layout.
+///
+/// ```
+/// use tempo_storage_interop::{Packable, impl_transparent_storable};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// struct Timestamp(u64);
+///
+/// impl_transparent_storable!(Timestamp, u64);
+///
+/// assert_eq!(Timestamp(7).to_word(), 7u64.to_word());
+/// ```
+#[macro_export]
+macro_rules! impl_transparent_storable {
+    ($ty:ty, $inner:ty) => {
+        impl $crate::__private::OnlyPrimitives for $ty {}
+
+        impl $crate::StorableType for $ty {
+            const LAYOUT: $crate::Layout = <$inner as $crate::StorableType>::LAYOUT;
+            type Handler = $crate::Slot<Self>;
+
+            fn handle(slot: $crate::__private::U256, ctx: $crate::LayoutCtx) -> Self::Handler {
+                $crate::Slot::new_with_ctx(slot, ctx)
+            }
+        }
+
+        impl $crate::DescribeLayout for $ty {
+            fn storage_layout() -> $crate::StorageLayout {
+                <$inner as $crate::DescribeLayout>::storage_layout()
+            }
+        }
+
+        impl $crate::Packable for $ty {
+            fn to_word(&self) -> $crate::__private::U256 {
+                <$inner as $crate::Packable>::to_word(&self.0)
+            }
+
+            fn from_word(word: $crate::__private::U256) -> $crate::Result<Self> {
+                <$inner as $crate::Packable>::from_word(word).map(Self)
+            }
+
+            fn from_word_checked(word: $crate::__private::U256) -> $crate::Result<Self> {
+                <$inner as $crate::Packable>::from_word_checked(word).map(Self)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::U256;
+
+    use crate::{Handler, Result, Slot, StorableType, StorageOps, test_util::MemoryStorage};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Timestamp(u64);
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct BlockNumber(u64);
+
+    impl_transparent_storable!(Timestamp, u64);
+    impl_transparent_storable!(BlockNumber, u64);
+
+    #[test]
+    fn test_newtypes_pack_like_inner() -> Result<()> {
+        let mut storage = MemoryStorage::new();
+        let mut slot = Slot::<(Timestamp, BlockNumber, u128)>::new(U256::from(4));
+        let value = (Timestamp(1_700_000_000), BlockNumber(42), u128::MAX);
+        assert_eq!(Timestamp::LAYOUT, u64::LAYOUT);
+
+        slot.write(&mut storage, value)?;
+        assert_eq!(slot.read(&storage)?, value);
+        // all three fields share one slot, like (uint64, uint64, uint128)
+        assert_eq!(
+            Slot::<(u64, u64, u128)>::new(U256::from(4)).read(&storage)?,
+            (1_700_000_000, 42, u128::MAX)
+        );
+        assert_eq!(storage.load(U256::from(5))?, U256::ZERO);
+        Ok(())
+    }
+}
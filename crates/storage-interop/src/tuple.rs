@@ -0,0 +1,339 @@
+//! Tuples laid out like Solidity structs, one field after the other.
+//!
+//! Fields may be any [`Storable`]: value types are packed, while arrays, nested tuples and
+//! dynamic types occupy their own slots, exactly as `solc` places struct members.
+
+use alloy_primitives::U256;
+
+use crate::{
+    Result,
+    layout::{Layout, LayoutCtx, Storable, StorableType},
+    packing::{FieldLocation, PackedSlot, calc_field_locs, calc_struct_slots},
+    slot::Slot,
+    storage::StorageOps,
+};
+
+/// Decodes consecutive fields, loading each slot once.
+pub struct FieldReader<'a, S> {
+    storage: &'a S,
+    base_slot: U256,
+    cached: Option<(usize, PackedSlot)>,
+}
+
+impl<'a, S: StorageOps> FieldReader<'a, S> {
+    #[inline]
+    pub fn new(storage: &'a S, base_slot: U256) -> Self {
+        Self {
+            storage,
+            base_slot,
+            cached: None,
+        }
+    }
+
+    pub fn field<T: Storable>(&mut self, loc: FieldLocation) -> Result<T> {
+        if !T::IS_PACKABLE {
+            let slot = self.base_slot + U256::from(loc.offset_slots);
+            return T::load(self.storage, slot, LayoutCtx::FULL);
+        }
+
+        let slot = self.base_slot + U256::from(loc.offset_slots);
+        let word = match &self.cached {
+            Some((slot_idx, word)) if *slot_idx == loc.offset_slots => word,
+            _ => {
+                let value = self.storage.load(slot)?;
+                &self.cached.insert((loc.offset_slots, PackedSlot(value))).1
+            }
+        };
+
+        T::load(word, U256::ZERO, LayoutCtx::packed(loc.offset_bytes))
+            .map_err(|err| err.with_slot(slot))
+    }
+
+    /// Appends the slots backing the field at `loc` to `slots`, see [`Storable::raw_slots`].
+    ///
+    /// Packed fields sharing the previous field's slot add nothing.
+    pub fn raw_slots<T: Storable>(&self, loc: FieldLocation, slots: &mut Vec<U256>) -> Result<()> {
+        let slot = self.base_slot + U256::from(loc.offset_slots);
+        if !T::IS_PACKABLE {
+            slots.extend(T::raw_slots(self.storage, slot)?);
+        } else if slots.last() != Some(&slot) {
+            slots.push(slot);
+        }
+        Ok(())
+    }
+}
+
+/// Encodes consecutive fields, storing each slot once.
+///
+/// Slots holding packed fields are fully overwritten, so bytes not covered by any field end
+/// up zeroed. Non-packable fields are stored directly at their own slots.
+pub struct FieldWriter<'a, S> {
+    storage: &'a mut S,
+    base_slot: U256,
+    pending: Option<(usize, PackedSlot)>,
+}
+
+impl<'a, S: StorageOps> FieldWriter<'a, S> {
+    #[inline]
+    pub fn new(storage: &'a mut S, base_slot: U256) -> Self {
+        Self {
+            storage,
+            base_slot,
+            pending: None,
+        }
+    }
+
+    pub fn field<T: Storable>(&mut self, value: &T, loc: FieldLocation) -> Result<()> {
+        if !T::IS_PACKABLE {
+            self.flush()?;
+            let slot = self.base_slot + U256::from(loc.offset_slots);
+            return value.store(self.storage, slot, LayoutCtx::FULL);
+        }
+
+        if self
+            .pending
+            .as_ref()
+            .is_some_and(|(slot_idx, _)| *slot_idx != loc.offset_slots)
+        {
+            self.flush()?;
+        }
+
+        let (_, word) = self
+            .pending
+            .get_or_insert((loc.offset_slots, PackedSlot(U256::ZERO)));
+        value
+            .store(word, U256::ZERO, LayoutCtx::packed(loc.offset_bytes))
+            .map_err(|err| err.with_slot(self.base_slot + U256::from(loc.offset_slots)))
+    }
+
+    /// Deletes the field at `loc`, including the data region of dynamic fields.
+    pub fn clear<T: Storable>(&mut self, loc: FieldLocation) -> Result<()> {
+        if !T::IS_PACKABLE {
+            self.flush()?;
+            let slot = self.base_slot + U256::from(loc.offset_slots);
+            return T::delete(self.storage, slot, LayoutCtx::FULL);
+        }
+
+        if self
+            .pending
+            .as_ref()
+            .is_some_and(|(slot_idx, _)| *slot_idx != loc.offset_slots)
+        {
+            self.flush()?;
+        }
+        self.pending
+            .get_or_insert((loc.offset_slots, PackedSlot(U256::ZERO)));
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<()> {
+        self.flush()
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        match self.pending.take() {
+            Some((slot_idx, word)) => self
+                .storage
+                .store(self.base_slot + U256::from(slot_idx), word.0),
+            None => Ok(()),
+        }
+    }
+}
+
+macro_rules! impl_storable_tuple {
+    ($($idx:tt => $ty:ident),+) => {
+        impl<$($ty: Storable),+> StorableType for ($($ty,)+) {
+            const LAYOUT: Layout = Layout::Slots(calc_struct_slots([$($ty::LAYOUT),+]));
+            type Handler = Slot<Self>;
+
+            fn handle(slot: U256, ctx: LayoutCtx) -> Self::Handler {
+                Slot::new_with_ctx(slot, ctx)
+            }
+        }
+
+        impl<$($ty: Storable),+> Storable for ($($ty,)+) {
+            fn load<S: StorageOps>(storage: &S, slot: U256, ctx: LayoutCtx) -> Result<Self> {
+                debug_assert_eq!(ctx, LayoutCtx::FULL, "Tuples cannot be packed");
+                let locs = const { calc_field_locs([$($ty::LAYOUT),+]) };
+
+                let mut reader = FieldReader::new(storage, slot);
+                Ok(($(reader.field::<$ty>(locs[$idx])?,)+))
+            }
+
+            fn store<S: StorageOps>(
+                &self,
+                storage: &mut S,
+                slot: U256,
+                ctx: LayoutCtx,
+            ) -> Result<()> {
+                debug_assert_eq!(ctx, LayoutCtx::FULL, "Tuples cannot be packed");
+                let locs = const { calc_field_locs([$($ty::LAYOUT),+]) };
+
+                let mut writer = FieldWriter::new(storage, slot);
+                $(writer.field(&self.$idx, locs[$idx])?;)+
+                writer.finish()
+            }
+
+            fn delete<S: StorageOps>(storage: &mut S, slot: U256, ctx: LayoutCtx) -> Result<()> {
+                debug_assert_eq!(ctx, LayoutCtx::FULL, "Tuples cannot be packed");
+                let locs = const { calc_field_locs([$($ty::LAYOUT),+]) };
+
+                let mut writer = FieldWriter::new(storage, slot);
+                $(writer.clear::<$ty>(locs[$idx])?;)+
+                writer.finish()
+            }
+
+            fn raw_slots<S: StorageOps>(storage: &S, slot: U256) -> Result<Vec<U256>> {
+                let locs = const { calc_field_locs([$($ty::LAYOUT),+]) };
+
+                let reader = FieldReader::new(storage, slot);
+                let mut slots = Vec::with_capacity(Self::SLOTS);
+                $(reader.raw_slots::<$ty>(locs[$idx], &mut slots)?;)+
+                Ok(slots)
+            }
+        }
+    };
+}
+
+impl_storable_tuple!(0 => A, 1 => B);
+impl_storable_tuple!(0 => A, 1 => B, 2 => C);
+impl_storable_tuple!(0 => A, 1 => B, 2 => C, 3 => D);
+impl_storable_tuple!(0 => A, 1 => B, 2 => C, 3 => D, 4 => E);
+impl_storable_tuple!(0 => A, 1 => B, 2 => C, 3 => D, 4 => E, 5 => F);
+impl_storable_tuple!(0 => A, 1 => B, 2 => C, 3 => D, 4 => E, 5 => F, 6 => G);
+impl_storable_tuple!(0 => A, 1 => B, 2 => C, 3 => D, 4 => E, 5 => F, 6 => G, 7 => H);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{layout::Handler, test_util::MemoryStorage};
+    use alloy_primitives::Address;
+
+    #[test]
+    fn test_tuple_packs_into_single_slot() -> Result<()> {
+        type Packed = (u8, Address, u64);
+        assert_eq!(Packed::LAYOUT, Layout::Slots(1));
+
+        let mut storage = MemoryStorage::default();
+        let admin = Address::repeat_byte(0xaa);
+        let mut slot = Slot::<Packed>::new(U256::from(3));
+        slot.write(&mut storage, (7, admin, u64::MAX))?;
+
+        let expected = (U256::from(u64::MAX) << 168)
+            | (U256::from_be_slice(admin.as_slice()) << 8)
+            | U256::from(7);
+        assert_eq!(storage.dump_nonzero(), vec![(U256::from(3), expected)]);
+        assert_eq!(slot.read(&storage)?, (7, admin, u64::MAX));
+        Ok(())
+    }
+
+    #[test]
+    fn test_tuple_spills_to_next_slot() -> Result<()> {
+        type Spilled = (U256, u8);
+        assert_eq!(Spilled::LAYOUT, Layout::Slots(2));
+
+        let mut storage = MemoryStorage::default();
+        let mut slot = Slot::<Spilled>::new(U256::ZERO);
+        slot.write(&mut storage, (U256::MAX, 0x42))?;
+
+        assert_eq!(storage.load(U256::ZERO)?, U256::MAX);
+        assert_eq!(storage.load(U256::ONE)?, U256::from(0x42));
+        assert_eq!(slot.read(&storage)?, (U256::MAX, 0x42));
+
+        slot.delete(&mut storage)?;
+        assert!(storage.dump_nonzero().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_raw_slots_include_dynamic_fields() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+        let mut slot = Slot::<(U256, Vec<u8>)>::new(U256::from(3));
+        slot.write(&mut storage, (U256::from(7), vec![1; 40]))?;
+
+        // the head slot, the length slot, then two data slots of the byte array
+        let data = crate::data_region_slot(U256::from(4));
+        let slots = <(U256, Vec<u8>)>::raw_slots(&storage, U256::from(3))?;
+        assert_eq!(
+            slots,
+            [U256::from(3), U256::from(4), data, data + U256::ONE]
+        );
+        assert_eq!(slot.read_raw(&storage)?.len(), 4);
+
+        // packed fields share one slot, arrays recurse into their elements
+        let slots = <(u8, u64, [String; 2])>::raw_slots(&storage, U256::ZERO)?;
+        assert_eq!(slots, [U256::ZERO, U256::ONE, U256::from(2)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_tuple_with_fixed_array_field() -> Result<()> {
+        // struct S { uint8[4] flags; address admin; }
+        //
+        // `forge inspect S storage-layout` places `flags` at slot 0 and `admin` at slot 1,
+        // offset 0: arrays always start a new slot, and so does the member following them.
+        type WithArray = ([u8; 4], Address);
+        assert_eq!(WithArray::LAYOUT, Layout::Slots(2));
+
+        let mut storage = MemoryStorage::default();
+        let admin = Address::repeat_byte(0xcc);
+        let mut slot = Slot::<WithArray>::new(U256::from(8));
+        slot.write(&mut storage, ([1, 2, 3, 4], admin))?;
+
+        assert_eq!(storage.load(U256::from(8))?, U256::from(0x04030201u32));
+        assert_eq!(
+            storage.load(U256::from(9))?,
+            U256::from_be_slice(admin.as_slice())
+        );
+        assert_eq!(slot.read(&storage)?, ([1, 2, 3, 4], admin));
+        Ok(())
+    }
+
+    #[test]
+    fn test_tuple_value_after_array_starts_new_slot() -> Result<()> {
+        // struct S { uint8 a; uint16[2] b; uint8 c; } -> a: slot 0, b: slot 1, c: slot 2
+        type Mixed = (u8, [u16; 2], u8);
+        assert_eq!(Mixed::LAYOUT, Layout::Slots(3));
+
+        let mut storage = MemoryStorage::default();
+        let mut slot = Slot::<Mixed>::new(U256::ZERO);
+        slot.write(&mut storage, (0x11, [0x2222, 0x3333], 0x44))?;
+
+        assert_eq!(
+            storage.dump_nonzero(),
+            vec![
+                (U256::ZERO, U256::from(0x11)),
+                (U256::ONE, U256::from(0x3333_2222u32)),
+                (U256::from(2), U256::from(0x44)),
+            ]
+        );
+        assert_eq!(slot.read(&storage)?, (0x11, [0x2222, 0x3333], 0x44));
+        Ok(())
+    }
+
+    #[test]
+    fn test_tuple_delete_clears_dynamic_fields() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+        let mut slot = Slot::<(u64, String)>::new(U256::ZERO);
+        slot.write(&mut storage, (1, "x".repeat(100)))?;
+        assert_eq!(storage.dump_nonzero().len(), 2 + 4);
+
+        slot.delete(&mut storage)?;
+        assert!(storage.dump_nonzero().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_field_overflow_reports_field_slot() {
+        let storage = MemoryStorage::default();
+        let err = FieldReader::new(&storage, U256::from(0x10))
+            .field::<u64>(FieldLocation::new(2, 28, 8))
+            .unwrap_err();
+
+        // the tuple's handler must not overwrite it with the tuple's base slot
+        assert_eq!(
+            err.with_slot(U256::from(0x10)).to_string(),
+            "packed value spans slot boundary: slot=0x12, offset=28, bytes=8"
+        );
+    }
+}
@@ -1,8 +1,9 @@
-use alloy_primitives::{Address, U256};
+use alloy_primitives::{Address, U256, U512, aliases::*};
 
 use crate::{
-    layout::{Layout, Packable, StorableType},
+    layout::{Layout, LayoutCtx, Packable, Storable, StorableType},
     slot::Slot,
+    storage::StorageOps,
     InteropError,
     Result,
 };
@@ -74,6 +75,35 @@ macro_rules! impl_signed_packable {
     };
 }
 
+/// Implements `Packable` for alloy `Uint` aliases, stored like Solidity's `uintN`.
+///
+/// A blanket impl over all `Uint<BITS, LIMBS>` would overlap with the two-slot `U512` impl, so
+/// the packable widths are listed explicitly instead.
+macro_rules! impl_uint_packable {
+    ($($ty:ty),+ $(,)?) => {$(
+        impl sealed::OnlyPrimitives for $ty {}
+
+        impl StorableType for $ty {
+            const LAYOUT: Layout = Layout::Bytes(<$ty>::BITS / 8);
+            type Handler = Slot<Self>;
+
+            fn handle(slot: U256, ctx: crate::LayoutCtx) -> Self::Handler {
+                Slot::new_with_ctx(slot, ctx)
+            }
+        }
+
+        impl Packable for $ty {
+            fn to_word(&self) -> U256 {
+                U256::from(*self)
+            }
+
+            fn from_word(word: U256) -> Result<Self> {
+                Ok(Self::wrapping_from(word))
+            }
+        }
+    )+};
+}
+
 impl sealed::OnlyPrimitives for bool {}
 
 impl StorableType for bool {
@@ -104,6 +134,29 @@ impl Packable for bool {
     }
 }
 
+impl sealed::OnlyPrimitives for char {}
+
+/// Stored as its Unicode scalar value, like a `uint32`.
+impl StorableType for char {
+    const LAYOUT: Layout = Layout::Bytes(4);
+    type Handler = Slot<Self>;
+
+    fn handle(slot: U256, ctx: crate::LayoutCtx) -> Self::Handler {
+        Slot::new_with_ctx(slot, ctx)
+    }
+}
+
+impl Packable for char {
+    fn to_word(&self) -> U256 {
+        U256::from(u32::from(*self))
+    }
+
+    fn from_word(word: U256) -> Result<Self> {
+        let code_point = u32::from_word(word)?;
+        char::from_u32(code_point).ok_or(InteropError::InvalidChar(code_point))
+    }
+}
+
 impl sealed::OnlyPrimitives for Address {}
 
 impl StorableType for Address {
@@ -126,6 +179,13 @@ impl Packable for Address {
         addr.copy_from_slice(&bytes[12..]);
         Ok(Address::from(addr))
     }
+
+    fn from_word_checked(word: U256) -> Result<Self> {
+        if word >> 160 != U256::ZERO {
+            return Err(InteropError::DirtyAddressWord(word));
+        }
+        <Self as Packable>::from_word(word)
+    }
 }
 
 impl sealed::OnlyPrimitives for U256 {}
@@ -149,14 +209,140 @@ impl Packable for U256 {
     }
 }
 
+/// Stored across two slots, high word first.
+impl StorableType for U512 {
+    const LAYOUT: Layout = Layout::Slots(2);
+    type Handler = Slot<Self>;
+
+    fn handle(slot: U256, ctx: crate::LayoutCtx) -> Self::Handler {
+        debug_assert_eq!(ctx, LayoutCtx::FULL, "U512 cannot be packed");
+        Slot::new(slot)
+    }
+}
+
+impl Storable for U512 {
+    fn load<S: StorageOps>(storage: &S, slot: U256, ctx: LayoutCtx) -> Result<Self> {
+        debug_assert_eq!(ctx, LayoutCtx::FULL, "U512 cannot be packed");
+
+        let (high, low) = storage.load_u256_pair(slot)?;
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(&high.to_be_bytes::<32>());
+        bytes[32..].copy_from_slice(&low.to_be_bytes::<32>());
+        Ok(Self::from_be_bytes(bytes))
+    }
+
+    fn store<S: StorageOps>(&self, storage: &mut S, slot: U256, ctx: LayoutCtx) -> Result<()> {
+        debug_assert_eq!(ctx, LayoutCtx::FULL, "U512 cannot be packed");
+
+        let bytes = self.to_be_bytes::<64>();
+        storage.store(slot, U256::from_be_slice(&bytes[..32]))?;
+        storage.store(slot + U256::ONE, U256::from_be_slice(&bytes[32..]))
+    }
+}
+
 impl_unsigned_packable!(u8, 1);
 impl_unsigned_packable!(u16, 2);
 impl_unsigned_packable!(u32, 4);
 impl_unsigned_packable!(u64, 8);
 impl_unsigned_packable!(u128, 16);
 
+impl_uint_packable!(
+    U8, U16, U24, U32, U40, U48, U56, U64, U72, U80, U88, U96, U104, U112, U120, U128, U136, U144,
+    U152, U160, U168, U176, U184, U192, U200, U208, U216, U224, U232, U240, U248,
+);
+
 impl_signed_packable!(i8, 1);
 impl_signed_packable!(i16, 2);
 impl_signed_packable!(i32, 4);
 impl_signed_packable!(i64, 8);
 impl_signed_packable!(i128, 16);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Handler, packing, test_util::MemoryStorage};
+
+    #[test]
+    fn test_address_from_word_checked() {
+        let address = Address::repeat_byte(0xab);
+        let clean = Packable::to_word(&address);
+        assert_eq!(Address::from_word_checked(clean).unwrap(), address);
+
+        let dirty = clean | (U256::ONE << 200);
+        assert_eq!(<Address as Packable>::from_word(dirty).unwrap(), address);
+        assert!(matches!(
+            Address::from_word_checked(dirty),
+            Err(InteropError::DirtyAddressWord(word)) if word == dirty
+        ));
+    }
+
+    #[test]
+    fn test_u512_two_slots() -> Result<()> {
+        let mut storage = MemoryStorage::new();
+        let high = U256::from(0xdead_u64);
+        let low = U256::MAX - U256::from(1);
+        let value = (U512::from(high) << 256) | U512::from(low);
+
+        let mut slot = Slot::<U512>::new(U256::from(4));
+        slot.write(&mut storage, value)?;
+        assert_eq!(
+            storage.dump_nonzero(),
+            vec![(U256::from(4), high), (U256::from(5), low)]
+        );
+        assert_eq!(slot.read(&storage)?, value);
+
+        slot.delete(&mut storage)?;
+        assert!(storage.dump_nonzero().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_custom_width_uint_round_trip() -> Result<()> {
+        let mut storage = MemoryStorage::new();
+
+        let u40 = U40::MAX - U40::from(7);
+        let mut slot = Slot::<U40>::new(U256::ONE);
+        slot.write(&mut storage, u40)?;
+        assert_eq!(slot.read(&storage)?, u40);
+        assert_eq!(storage.load(U256::ONE)?, U256::from(u40));
+
+        // uint96 packed after an address, as in `struct { address a; uint96 b; }`
+        let u96 = U96::from(0x0123_4567_89ab_cdef_u64) << 32;
+        let word = packing::insert_packed_value(U256::ZERO, &Address::repeat_byte(0xcc), 0, 20)?;
+        let word = packing::insert_packed_value(word, &u96, 20, U96::BYTES)?;
+        assert_eq!(word >> 160, U256::from(u96));
+        assert_eq!(packing::extract_packed_value::<U96>(word, 20, 12)?, u96);
+
+        let u248 = U248::MAX;
+        let mut slot = Slot::<U248>::new(U256::from(2));
+        slot.write(&mut storage, u248)?;
+        assert_eq!(storage.load(U256::from(2))?, U256::MAX >> 8);
+        assert_eq!(slot.read(&storage)?, u248);
+
+        // words wider than the type are truncated like the primitive integers
+        assert_eq!(U40::from_word(U256::MAX)?, U40::MAX);
+        Ok(())
+    }
+
+    #[test]
+    fn test_char_round_trip() -> Result<()> {
+        let mut storage = MemoryStorage::new();
+        let mut pair = Slot::<(char, char)>::new(U256::ONE);
+        pair.write(&mut storage, ('é', '🦀'))?;
+        assert_eq!(pair.read(&storage)?, ('é', '🦀'));
+        assert_eq!(
+            storage.load(U256::ONE)?,
+            U256::from(0xe9) | (U256::from(0x1f980) << 32)
+        );
+
+        // UTF-16 surrogates and values above U+10FFFF are not scalar values
+        for invalid in [0xd800u32, 0x11_0000] {
+            storage.store(U256::from(2), U256::from(invalid))?;
+            assert!(matches!(
+                Slot::<char>::new(U256::from(2)).read(&storage),
+                Err(InteropError::InvalidChar(code_point)) if code_point == invalid
+            ));
+        }
+        Ok(())
+    }
+}
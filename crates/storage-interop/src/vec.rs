@@ -1,11 +1,17 @@
-use alloy_primitives::{U256, keccak256};
+use alloy_primitives::U256;
 use std::marker::PhantomData;
 
 use crate::{
+    iter::ElementIter,
     layout::{Handler, Layout, LayoutCtx, Storable, StorableType},
-    packing::{PackedSlot, calc_element_loc, calc_packed_slot_count},
+    packing::{
+        PREALLOCATION_LIMIT, PackedSlot, calc_element_loc, calc_packed_slot_count,
+        data_region_slot, decode_length, element_slot_offset, fixed_array_element_slot,
+        write_packed_element,
+    },
     slot::Slot,
-    storage::StorageOps,
+    storage::{SkipZeroWrites, StorageOps, prefetch_range},
+    InteropError,
     Result,
 };
 
@@ -17,7 +23,8 @@ where
     const IS_DYNAMIC: bool = true;
     type Handler = VecHandler<T>;
 
-    fn handle(slot: U256, _ctx: LayoutCtx) -> Self::Handler {
+    fn handle(slot: U256, ctx: LayoutCtx) -> Self::Handler {
+        debug_assert_eq!(ctx, LayoutCtx::FULL, "Dynamic arrays cannot be packed");
         VecHandler::new(slot)
     }
 }
@@ -30,16 +37,20 @@ where
         debug_assert_eq!(ctx, LayoutCtx::FULL, "Dynamic arrays cannot be packed");
 
         let length_value = storage.load(len_slot)?;
-        let length = length_value.to::<usize>();
+        let length = decode_length(length_value)?;
 
         if length == 0 {
             return Ok(Self::new());
         }
 
-        let data_start = calc_data_slot(len_slot);
+        let data_start = data_region_slot(len_slot);
         if T::BYTES <= 16 {
+            let slot_count = calc_packed_slot_count(length, T::BYTES);
+            prefetch_range(storage, data_start, slot_count)?;
             load_packed_elements(storage, data_start, length, T::BYTES)
         } else {
+            // the hint is capped anyway, so saturating is enough to avoid an overflow
+            prefetch_range(storage, data_start, length.saturating_mul(T::SLOTS))?;
             load_unpacked_elements(storage, data_start, length)
         }
     }
@@ -47,25 +58,25 @@ where
     fn store<S: StorageOps>(&self, storage: &mut S, len_slot: U256, ctx: LayoutCtx) -> Result<()> {
         debug_assert_eq!(ctx, LayoutCtx::FULL, "Dynamic arrays cannot be packed");
 
+        let old_length = decode_length(storage.load(len_slot)?)?;
         storage.store(len_slot, U256::from(self.len()))?;
 
-        if self.is_empty() {
-            return Ok(());
-        }
-
-        let data_start = calc_data_slot(len_slot);
+        let data_start = data_region_slot(len_slot);
         if T::BYTES <= 16 {
-            store_packed_elements(self, storage, data_start, T::BYTES)
+            store_packed_elements(self, storage, data_start, T::BYTES)?;
         } else {
-            store_unpacked_elements(self, storage, data_start)
+            store_unpacked_elements(self, storage, data_start)?;
         }
+
+        // clear elements of a previous, longer value so they can't resurface
+        zero_tail_elements::<T, S>(storage, data_start, self.len(), old_length)
     }
 
     fn delete<S: StorageOps>(storage: &mut S, len_slot: U256, ctx: LayoutCtx) -> Result<()> {
         debug_assert_eq!(ctx, LayoutCtx::FULL, "Dynamic arrays cannot be packed");
 
         let length_value = storage.load(len_slot)?;
-        let length = length_value.to::<usize>();
+        let length = decode_length(length_value)?;
 
         storage.store(len_slot, U256::ZERO)?;
 
@@ -73,7 +84,7 @@ where
             return Ok(());
         }
 
-        let data_start = calc_data_slot(len_slot);
+        let data_start = data_region_slot(len_slot);
         if T::BYTES <= 16 {
             let slot_count = calc_packed_slot_count(length, T::BYTES);
             for slot_idx in 0..slot_count {
@@ -81,13 +92,31 @@ where
             }
         } else {
             for elem_idx in 0..length {
-                let elem_slot = data_start + U256::from(elem_idx * T::SLOTS);
+                let elem_slot = data_start + element_slot_offset(elem_idx, T::SLOTS);
                 T::delete(storage, elem_slot, LayoutCtx::FULL)?;
             }
         }
 
         Ok(())
     }
+
+    fn raw_slots<S: StorageOps>(storage: &S, len_slot: U256) -> Result<Vec<U256>> {
+        let length = decode_length(storage.load(len_slot)?)?;
+        let data_start = data_region_slot(len_slot);
+        let mut slots = vec![len_slot];
+
+        if T::BYTES <= 16 {
+            let slot_count = calc_packed_slot_count(length, T::BYTES);
+            slots.extend((0..slot_count).map(|i| data_start + U256::from(i)));
+        } else {
+            for elem_idx in 0..length {
+                let elem_slot = data_start + element_slot_offset(elem_idx, T::SLOTS);
+                slots.extend(T::raw_slots(storage, elem_slot)?);
+            }
+        }
+
+        Ok(slots)
+    }
 }
 
 pub struct VecHandler<T>
@@ -98,6 +127,12 @@ where
     _ty: PhantomData<T>,
 }
 
+impl<T: Storable> Default for VecHandler<T> {
+    fn default() -> Self {
+        Self::new(U256::ZERO)
+    }
+}
+
 impl<T> Handler<Vec<T>> for VecHandler<T>
 where
     T: Storable,
@@ -127,6 +162,12 @@ where
         }
     }
 
+    /// Equivalent to [`VecHandler::new`], with the length stored at `slot`.
+    #[inline]
+    pub fn at_slot(slot: U256) -> Self {
+        Self::new(slot)
+    }
+
     #[inline]
     pub fn len_slot(&self) -> U256 {
         self.len_slot
@@ -134,7 +175,7 @@ where
 
     #[inline]
     pub fn data_slot(&self) -> U256 {
-        calc_data_slot(self.len_slot)
+        data_region_slot(self.len_slot)
     }
 
     #[inline]
@@ -145,7 +186,7 @@ where
     #[inline]
     pub fn len<S: StorageOps>(&self, storage: &S) -> Result<usize> {
         let slot = Slot::<U256>::new(self.len_slot);
-        Ok(slot.read(storage)?.to::<usize>())
+        decode_length(slot.read(storage)?)
     }
 
     #[inline]
@@ -153,20 +194,54 @@ where
         Ok(self.len(storage)? == 0)
     }
 
+    /// Like `write`, but skips every store that would write a zero word.
+    ///
+    /// Only correct when all slots covered by `value` are known to be zero already (e.g. first
+    /// initialization). Otherwise stale non-zero data is left in place of zero elements.
+    pub fn store_nonzero_only<S: StorageOps>(
+        &mut self,
+        storage: &mut S,
+        value: Vec<T>,
+    ) -> Result<()> {
+        value.store(
+            &mut SkipZeroWrites::new(storage),
+            self.len_slot,
+            LayoutCtx::FULL,
+        )
+    }
+
+    /// Returns how many data slots the elements occupy, reading only the length.
+    ///
+    /// Fails with [`InteropError::InvalidLength`] if the count does not fit in a `usize`.
+    pub fn data_slot_count<S: StorageOps>(&self, storage: &S) -> Result<usize> {
+        let length = self.len(storage)?;
+        if T::BYTES <= 16 {
+            Ok(calc_packed_slot_count(length, T::BYTES))
+        } else {
+            length
+                .checked_mul(T::SLOTS)
+                .ok_or(InteropError::InvalidLength {
+                    claimed: U256::from(length),
+                    max: usize::MAX / T::SLOTS,
+                })
+        }
+    }
+
+    /// Returns the slot and layout context of the element at `index`, without bounds checks.
     #[inline]
-    pub fn at_unchecked(&self, index: usize) -> T::Handler {
-        let data_start = self.data_slot();
-
-        let (base_slot, layout_ctx) = if T::BYTES <= 16 {
-            let location = calc_element_loc(index, T::BYTES);
-            (
-                data_start + U256::from(location.offset_slots),
-                LayoutCtx::packed(location.offset_bytes),
-            )
+    pub fn element_location(&self, index: usize) -> (U256, LayoutCtx) {
+        let (slot, offset) = dynamic_array_element_slot(self.len_slot, index, T::BYTES);
+
+        if T::BYTES <= 16 {
+            (slot, LayoutCtx::packed(offset))
         } else {
-            (data_start + U256::from(index * T::SLOTS), LayoutCtx::FULL)
-        };
+            (slot, LayoutCtx::FULL)
+        }
+    }
 
+    #[inline]
+    pub fn at_unchecked(&self, index: usize) -> T::Handler {
+        let (base_slot, layout_ctx) = self.element_location(index);
         T::handle(base_slot, layout_ctx)
     }
 
@@ -179,11 +254,109 @@ where
 
         Ok(Some(self.at_unchecked(index)))
     }
+
+    /// Overwrites the element at `index`, leaving packed neighbours untouched.
+    pub fn set<S: StorageOps>(&mut self, storage: &mut S, index: usize, value: T) -> Result<()> {
+        let length = self.len(storage)?;
+        if index >= length {
+            return Err(InteropError::IndexOutOfBounds { index, len: length });
+        }
+
+        self.write_element(storage, index, &value)
+    }
+
+    /// Appends `value` after the last element and bumps the stored length.
+    pub fn push<S: StorageOps>(&mut self, storage: &mut S, value: T) -> Result<()> {
+        let length = self.len(storage)?;
+
+        self.write_element(storage, length, &value)?;
+        storage.store(self.len_slot, U256::from(length + 1))
+    }
+
+    fn write_element<S: StorageOps>(&self, storage: &mut S, index: usize, value: &T) -> Result<()> {
+        if T::BYTES <= 16 {
+            write_packed_element(storage, self.data_slot(), index, T::BYTES, value)
+        } else {
+            let (slot, ctx) = self.element_location(index);
+            value.store(storage, slot, ctx)
+        }
+    }
+
+    /// Removes the element at `index` by moving the last element into its place, without
+    /// preserving order. The vacated tail element is zeroed.
+    pub fn swap_remove<S: StorageOps>(&mut self, storage: &mut S, index: usize) -> Result<T> {
+        let length = self.len(storage)?;
+        if index >= length {
+            return Err(InteropError::IndexOutOfBounds { index, len: length });
+        }
+
+        let (slot, ctx) = self.element_location(index);
+        let removed = T::load(storage, slot, ctx)?;
+
+        let last = length - 1;
+        let (last_slot, last_ctx) = self.element_location(last);
+        if index != last {
+            let moved = T::load(storage, last_slot, last_ctx)?;
+            moved.store(storage, slot, ctx)?;
+        }
+
+        T::delete(storage, last_slot, last_ctx)?;
+        storage.store(self.len_slot, U256::from(last))?;
+        Ok(removed)
+    }
+
+    /// Iterates over the elements in slot order, loading each packed slot only once.
+    #[inline]
+    pub fn iter<'a, S: StorageOps>(&self, storage: &'a S) -> Result<ElementIter<'a, T, S>> {
+        let length = self.len(storage)?;
+        Ok(ElementIter::new(storage, self.data_slot(), length))
+    }
 }
 
+/// Returns the slot and byte offset of element `index` of a dynamic array whose length is
+/// stored at `len_slot`, applying the same packing rules as `VecHandler`.
 #[inline]
-fn calc_data_slot(len_slot: U256) -> U256 {
-    U256::from_be_bytes(keccak256(len_slot.to_be_bytes::<32>()).0)
+pub fn dynamic_array_element_slot(
+    len_slot: U256,
+    index: usize,
+    elem_bytes: usize,
+) -> (U256, usize) {
+    fixed_array_element_slot(data_region_slot(len_slot), index, elem_bytes)
+}
+
+/// Zeroes the elements `new_length..old_length` of the data region starting at `data_start`.
+///
+/// Packed slots shared with surviving elements are left alone, since the preceding store
+/// already rewrote them in full.
+///
+/// This costs one store per stale slot, so shrinking a long array is as expensive as deleting
+/// its tail; `old_length` is bounded by [`MAX_DYNAMIC_LENGTH`](crate::MAX_DYNAMIC_LENGTH) via
+/// [`decode_length`], and every slot cleared here was paid for by the write that grew the
+/// array. Reading the old length adds no gas after EIP-2929, since it warms the length slot
+/// that is stored right after.
+fn zero_tail_elements<T, S>(
+    storage: &mut S,
+    data_start: U256,
+    new_length: usize,
+    old_length: usize,
+) -> Result<()>
+where
+    T: Storable,
+    S: StorageOps,
+{
+    if T::BYTES <= 16 {
+        let first = calc_packed_slot_count(new_length, T::BYTES);
+        for slot_idx in first..calc_packed_slot_count(old_length, T::BYTES) {
+            storage.store(data_start + U256::from(slot_idx), U256::ZERO)?;
+        }
+    } else {
+        for elem_idx in new_length..old_length {
+            let elem_slot = data_start + element_slot_offset(elem_idx, T::SLOTS);
+            T::delete(storage, elem_slot, LayoutCtx::FULL)?;
+        }
+    }
+
+    Ok(())
 }
 
 fn load_packed_elements<T, S>(
@@ -196,25 +369,23 @@ where
     T: Storable,
     S: StorageOps,
 {
-    let slot_count = calc_packed_slot_count(length, byte_count);
-    let mut elements = Vec::with_capacity(length);
-    let mut current_index = 0;
-
-    for slot_idx in 0..slot_count {
-        let slot_value = storage.load(data_start + U256::from(slot_idx))?;
-        let slot_packed = PackedSlot(slot_value);
-
-        let elements_in_slot = ((length - current_index) * byte_count).min(32) / byte_count;
-        for offset in 0..elements_in_slot {
-            let elem = T::load(
-                &slot_packed,
-                U256::ZERO,
-                LayoutCtx::packed(offset * byte_count),
-            )?;
-            elements.push(elem);
-        }
+    let mut elements = Vec::with_capacity(length.min(PREALLOCATION_LIMIT));
+    let mut slot_packed = PackedSlot(U256::ZERO);
 
-        current_index += elements_in_slot;
+    for index in 0..length {
+        let loc = calc_element_loc(index, byte_count);
+        // each slot is loaded once, when its first element is reached
+        let slot = data_start + U256::from(loc.offset_slots);
+        if loc.offset_bytes == 0 {
+            slot_packed = PackedSlot(storage.load(slot)?);
+        }
+        let element = T::load(
+            &slot_packed,
+            U256::ZERO,
+            LayoutCtx::packed(loc.offset_bytes),
+        )
+        .map_err(|err| err.with_slot(slot))?;
+        elements.push(element);
     }
 
     Ok(elements)
@@ -230,34 +401,29 @@ where
     T: Storable,
     S: StorageOps,
 {
-    let slot_count = calc_packed_slot_count(elements.len(), byte_count);
+    let mut slot_packed = PackedSlot(U256::ZERO);
 
-    for slot_idx in 0..slot_count {
-        let start_elem = slot_idx * (32 / byte_count);
-        let end_elem = (start_elem + (32 / byte_count)).min(elements.len());
-
-        let slot_value = build_packed_slot(&elements[start_elem..end_elem], byte_count)?;
-        storage.store(data_start + U256::from(slot_idx), slot_value)?;
+    for (index, elem) in elements.iter().enumerate() {
+        let loc = calc_element_loc(index, byte_count);
+        let slot = data_start + U256::from(loc.offset_slots);
+        elem.store(
+            &mut slot_packed,
+            U256::ZERO,
+            LayoutCtx::packed(loc.offset_bytes),
+        )
+        .map_err(|err| err.with_slot(slot))?;
+
+        // flush once the slot is full or the last element has been packed
+        let next = calc_element_loc(index + 1, byte_count);
+        if next.offset_bytes == 0 || index + 1 == elements.len() {
+            storage.store(slot, slot_packed.0)?;
+            slot_packed = PackedSlot(U256::ZERO);
+        }
     }
 
     Ok(())
 }
 
-fn build_packed_slot<T>(elements: &[T], byte_count: usize) -> Result<U256>
-where
-    T: Storable,
-{
-    let mut slot_value = PackedSlot(U256::ZERO);
-    let mut current_offset = 0;
-
-    for elem in elements {
-        elem.store(&mut slot_value, U256::ZERO, LayoutCtx::packed(current_offset))?;
-        current_offset += byte_count;
-    }
-
-    Ok(slot_value.0)
-}
-
 fn load_unpacked_elements<T, S>(
     storage: &S,
     data_start: U256,
@@ -267,10 +433,10 @@ where
     T: Storable,
     S: StorageOps,
 {
-    let mut elements = Vec::with_capacity(length);
+    let mut elements = Vec::with_capacity(length.min(PREALLOCATION_LIMIT));
 
     for index in 0..length {
-        let slot = data_start + U256::from(index * T::SLOTS);
+        let slot = data_start + element_slot_offset(index, T::SLOTS);
         let elem = T::load(storage, slot, LayoutCtx::FULL)?;
         elements.push(elem);
     }
@@ -288,9 +454,381 @@ where
     S: StorageOps,
 {
     for (index, elem) in elements.iter().enumerate() {
-        let slot = data_start + U256::from(index * T::SLOTS);
+        let slot = data_start + element_slot_offset(index, T::SLOTS);
         elem.store(storage, slot, LayoutCtx::FULL)?;
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        packing::MAX_DYNAMIC_LENGTH,
+        test_util::{CountingStorage, MemoryStorage},
+    };
+    use alloy_primitives::Uint;
+    use proptest::prelude::*;
+    use std::cell::RefCell;
+
+    /// Records the slot lists passed to `prefetch`.
+    #[derive(Default)]
+    struct PrefetchLog {
+        inner: MemoryStorage,
+        prefetched: RefCell<Vec<Vec<U256>>>,
+    }
+
+    impl StorageOps for PrefetchLog {
+        fn load(&self, slot: U256) -> Result<U256> {
+            self.inner.load(slot)
+        }
+
+        fn store(&mut self, slot: U256, value: U256) -> Result<()> {
+            self.inner.store(slot, value)
+        }
+
+        fn prefetch(&self, slots: &[U256]) -> Result<()> {
+            self.prefetched.borrow_mut().push(slots.to_vec());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_load_prefetches_data_region() -> Result<()> {
+        let mut storage = PrefetchLog::default();
+        let mut handler = VecHandler::<U256>::new(U256::from(2));
+        let values: Vec<_> = (1..=10).map(U256::from).collect();
+        handler.write(&mut storage, values.clone())?;
+        assert!(storage.prefetched.borrow().is_empty());
+
+        assert_eq!(handler.read(&storage)?, values);
+        let data_start = handler.data_slot();
+        let expected: Vec<_> = (0..10).map(|i| data_start + U256::from(i)).collect();
+        assert_eq!(*storage.prefetched.borrow(), [expected]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_huge_claimed_length_is_not_preallocated() -> Result<()> {
+        /// Storage whose data region ends after two readable slots.
+        struct Truncated(MemoryStorage);
+
+        impl StorageOps for Truncated {
+            fn load(&self, slot: U256) -> Result<U256> {
+                if slot >= data_region_slot(U256::ZERO) + U256::from(2) {
+                    let reason = format!("slot {slot} is missing");
+                    return Err(InteropError::RuntimeError(reason));
+                }
+                self.0.load(slot)
+            }
+
+            fn store(&mut self, slot: U256, value: U256) -> Result<()> {
+                self.0.store(slot, value)
+            }
+        }
+
+        let mut storage = Truncated(MemoryStorage::new());
+        storage.store(U256::ZERO, U256::from(MAX_DYNAMIC_LENGTH))?;
+        assert!(matches!(
+            Vec::<U256>::load(&storage, U256::ZERO, LayoutCtx::FULL),
+            Err(InteropError::RuntimeError(_))
+        ));
+        assert!(matches!(
+            Vec::<u8>::load(&storage, U256::ZERO, LayoutCtx::FULL),
+            Err(InteropError::RuntimeError(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_iter_loads_each_packed_slot_once() -> Result<()> {
+        let mut storage = CountingStorage::default();
+        let mut handler = VecHandler::<u16>::new(U256::from(2));
+        let values: Vec<u16> = (0..40).collect();
+        handler.write(&mut storage, values.clone())?;
+
+        storage.reset();
+        let loaded = handler.iter(&storage)?.collect::<Result<Vec<_>>>()?;
+        assert_eq!(loaded, values);
+        // one length read plus three packed data slots
+        assert_eq!(storage.loads(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_nonzero_only_skips_zero_elements() -> Result<()> {
+        let mut storage = CountingStorage::default();
+        let mut handler = VecHandler::<U256>::new(U256::ZERO);
+        let values = [1u64, 0, 3, 0, 5, 0, 7, 0].map(U256::from).to_vec();
+
+        handler.store_nonzero_only(&mut storage, values.clone())?;
+        // four non-zero elements plus the length
+        assert_eq!(storage.stores(), 5);
+        assert_eq!(handler.read(&storage)?, values);
+        Ok(())
+    }
+
+    #[test]
+    fn test_data_slot_count() -> Result<()> {
+        let mut storage = CountingStorage::default();
+        let mut packed = VecHandler::<u16>::new(U256::ZERO);
+        assert_eq!(packed.data_slot_count(&storage)?, 0);
+        packed.write(&mut storage, (0..100).collect())?;
+
+        storage.reset();
+        // 16 elements per slot
+        assert_eq!(packed.data_slot_count(&storage)?, 7);
+        assert_eq!(storage.loads(), 1);
+
+        let mut unpacked = VecHandler::<U256>::new(U256::ONE);
+        unpacked.write(&mut storage, vec![U256::MAX; 3])?;
+        assert_eq!(unpacked.data_slot_count(&storage)?, 3);
+
+        let huge = VecHandler::<[U256; 1 << 40]>::new(U256::from(2));
+        storage.store(U256::from(2), U256::from(MAX_DYNAMIC_LENGTH))?;
+        assert!(matches!(
+            huge.data_slot_count(&storage),
+            Err(InteropError::InvalidLength { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_dynamic_array_element_slot_matches_handler() {
+        let len_slot = U256::from(4);
+        let data_start = data_region_slot(len_slot);
+
+        let packed = VecHandler::<u64>::new(len_slot);
+        for index in [0, 3, 4, 9] {
+            let (slot, ctx) = packed.element_location(index);
+            assert_eq!(
+                dynamic_array_element_slot(len_slot, index, 8),
+                (slot, ctx.packed_offset().unwrap())
+            );
+        }
+        assert_eq!(
+            dynamic_array_element_slot(len_slot, 5, 8),
+            (data_start + U256::ONE, 8)
+        );
+
+        let unpacked = VecHandler::<U256>::new(len_slot);
+        for index in [0, 1, 7] {
+            let (slot, ctx) = unpacked.element_location(index);
+            assert_eq!(ctx, LayoutCtx::FULL);
+            assert_eq!(dynamic_array_element_slot(len_slot, index, 32), (slot, 0));
+        }
+        assert_eq!(
+            dynamic_array_element_slot(len_slot, 7, 32),
+            (data_start + U256::from(7), 0)
+        );
+    }
+
+    #[test]
+    fn test_swap_remove_matches_vec() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+        let mut handler = VecHandler::<u64>::new(U256::from(9));
+        let mut expected: Vec<u64> = (1..=10).collect();
+        handler.write(&mut storage, expected.clone())?;
+
+        for index in [2, 8, 0, 6] {
+            assert_eq!(
+                handler.swap_remove(&mut storage, index)?,
+                expected.swap_remove(index)
+            );
+            assert_eq!(handler.read(&storage)?, expected);
+        }
+
+        // the vacated tail bytes are cleared, so the data matches a fresh write
+        let mut fresh = MemoryStorage::default();
+        VecHandler::<u64>::new(U256::from(9)).write(&mut fresh, expected.clone())?;
+        assert_eq!(storage, fresh);
+
+        assert!(matches!(
+            handler.swap_remove(&mut storage, 6),
+            Err(InteropError::IndexOutOfBounds { index: 6, len: 6 })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_swap_remove_unpacked() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+        let mut handler = VecHandler::<U256>::new(U256::ZERO);
+        let mut expected = vec![U256::from(1), U256::from(2), U256::MAX];
+        handler.write(&mut storage, expected.clone())?;
+
+        assert_eq!(
+            handler.swap_remove(&mut storage, 0)?,
+            expected.swap_remove(0)
+        );
+        assert_eq!(handler.read(&storage)?, expected);
+        assert_eq!(
+            storage.load(handler.data_slot() + U256::from(2))?,
+            U256::ZERO
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_keeps_packed_neighbours() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+        let mut handler = VecHandler::<u32>::new(U256::from(3));
+        let mut expected: Vec<u32> = (1..=10).collect();
+        handler.write(&mut storage, expected.clone())?;
+
+        handler.set(&mut storage, 4, u32::MAX)?;
+        expected[4] = u32::MAX;
+        assert_eq!(handler.read(&storage)?, expected);
+
+        handler.push(&mut storage, 11)?;
+        expected.push(11);
+        assert_eq!(handler.read(&storage)?, expected);
+
+        assert!(matches!(
+            handler.set(&mut storage, 11, 0),
+            Err(InteropError::IndexOutOfBounds { index: 11, len: 11 })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_shrinking_write_zeroes_stale_tail() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+        let mut handler = VecHandler::<u64>::new(U256::from(6));
+        handler.write(&mut storage, (1..=100).collect())?;
+        handler.write(&mut storage, (1..=50).collect())?;
+
+        // 50 uint64s fill 12.5 slots, the old 100 filled 25
+        let data_start = handler.data_slot();
+        for slot_idx in 13..25 {
+            assert_eq!(storage.load(data_start + U256::from(slot_idx))?, U256::ZERO);
+        }
+        // the upper half of the shared last slot held elements 50 and 51
+        assert_eq!(
+            storage.load(data_start + U256::from(12))? >> 128,
+            U256::ZERO
+        );
+
+        let mut fresh = MemoryStorage::default();
+        VecHandler::<u64>::new(U256::from(6)).write(&mut fresh, (1..=50).collect())?;
+        assert_eq!(storage, fresh);
+
+        let mut nested = VecHandler::<Vec<u8>>::new(U256::from(7));
+        nested.write(&mut storage, vec![vec![1; 40], vec![2; 40]])?;
+        nested.write(&mut storage, vec![vec![1; 40]])?;
+        nested.write(&mut fresh, vec![vec![1; 40]])?;
+        assert_eq!(storage, fresh);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn test_element_slot_of_huge_index() {
+        // `index * SLOTS` overflows usize, the slot math must not
+        let index = usize::MAX / 2;
+        let handler = VecHandler::<[U256; 4]>::new(U256::from(3));
+        let (slot, ctx) = handler.element_location(index);
+
+        assert_eq!(
+            slot,
+            handler.data_slot() + U256::from(index) * U256::from(4)
+        );
+        assert_eq!(ctx, LayoutCtx::FULL);
+    }
+
+    #[test]
+    fn test_packed_lengths_up_to_100() -> Result<()> {
+        fn check<T: Storable + Clone + PartialEq + std::fmt::Debug>(
+            value: impl Fn(usize) -> T,
+        ) -> Result<()> {
+            for len in 0..=100 {
+                let values: Vec<T> = (0..len).map(&value).collect();
+                let mut storage = MemoryStorage::default();
+                let mut handler = VecHandler::new(U256::from(1));
+                handler.write(&mut storage, values.clone())?;
+
+                assert_eq!(handler.read(&storage)?, values, "length {len}");
+                assert_eq!(
+                    handler.data_slot_count(&storage)?,
+                    calc_packed_slot_count(len, T::BYTES)
+                );
+            }
+            Ok(())
+        }
+
+        // widths that do and don't divide 32
+        check(|i| i as u8)?;
+        check(|i| i as u128 * u64::MAX as u128)?;
+        check(|i| Uint::<24, 1>::from(i * 0x1_0001))?;
+        check(|i| Uint::<40, 1>::from(i * 0x1_0000_0001))?;
+        check(|i| Uint::<88, 2>::from(i).wrapping_sub(Uint::from(1)))?;
+        Ok(())
+    }
+
+    /// Generates a proptest writing random vecs over a longer previous value and reading them
+    /// back, both in full and element by element.
+    macro_rules! packed_round_trip {
+        ($($name:ident: $strategy:expr),* $(,)?) => {
+            proptest! {
+                #![proptest_config(ProptestConfig::with_cases(64))]
+                $(
+                    #[test]
+                    fn $name(
+                        previous in prop::collection::vec($strategy, 0..=100),
+                        values in prop::collection::vec($strategy, 0..=100),
+                    ) {
+                        let mut storage = MemoryStorage::default();
+                        let mut handler = VecHandler::new(U256::from(2));
+                        handler.write(&mut storage, previous)?;
+                        handler.write(&mut storage, values.clone())?;
+
+                        prop_assert_eq!(&handler.read(&storage)?, &values);
+                        for (index, value) in values.iter().enumerate() {
+                            prop_assert_eq!(&handler.at_unchecked(index).read(&storage)?, value);
+                        }
+
+                        let mut fresh = MemoryStorage::default();
+                        VecHandler::new(U256::from(2)).write(&mut fresh, values)?;
+                        prop_assert_eq!(storage, fresh);
+                    }
+                )*
+            }
+        };
+    }
+
+    fn uint<const BITS: usize, const LIMBS: usize>() -> impl Strategy<Value = Uint<BITS, LIMBS>> {
+        any::<u128>().prop_map(|value| Uint::from_be_slice(&value.to_be_bytes()[16 - BITS / 8..]))
+    }
+
+    packed_round_trip! {
+        test_packed_round_trip_bool: any::<bool>(),
+        test_packed_round_trip_char: any::<char>(),
+        test_packed_round_trip_u8: any::<u8>(),
+        test_packed_round_trip_u16: any::<u16>(),
+        test_packed_round_trip_u32: any::<u32>(),
+        test_packed_round_trip_u64: any::<u64>(),
+        test_packed_round_trip_u128: any::<u128>(),
+        test_packed_round_trip_i8: any::<i8>(),
+        test_packed_round_trip_i16: any::<i16>(),
+        test_packed_round_trip_i32: any::<i32>(),
+        test_packed_round_trip_i64: any::<i64>(),
+        test_packed_round_trip_i128: any::<i128>(),
+        test_packed_round_trip_uint8: uint::<8, 1>(),
+        test_packed_round_trip_uint16: uint::<16, 1>(),
+        test_packed_round_trip_uint24: uint::<24, 1>(),
+        test_packed_round_trip_uint32: uint::<32, 1>(),
+        test_packed_round_trip_uint40: uint::<40, 1>(),
+        test_packed_round_trip_uint48: uint::<48, 1>(),
+        test_packed_round_trip_uint56: uint::<56, 1>(),
+        test_packed_round_trip_uint64: uint::<64, 1>(),
+        test_packed_round_trip_uint72: uint::<72, 2>(),
+        test_packed_round_trip_uint80: uint::<80, 2>(),
+        test_packed_round_trip_uint88: uint::<88, 2>(),
+        test_packed_round_trip_uint96: uint::<96, 2>(),
+        test_packed_round_trip_uint104: uint::<104, 2>(),
+        test_packed_round_trip_uint112: uint::<112, 2>(),
+        test_packed_round_trip_uint120: uint::<120, 2>(),
+        test_packed_round_trip_uint128: uint::<128, 2>(),
+    }
+}